@@ -1,30 +1,539 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{Local, TimeZone};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
-    execute,
+    event::{self, DisableFocusChange, EnableFocusChange, Event, KeyCode, KeyEventKind},
+    execute, queue,
+    style::{Attribute, SetAttribute, SetBackgroundColor, SetForegroundColor},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use regex::Regex;
+use serde::Deserialize;
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Gauge, Paragraph, Row, Sparkline, Table, TableState, Wrap},
+    symbols,
+    widgets::{Axis, Bar, BarChart, BarGroup, Block, BorderType, Borders, Cell, Chart, Clear, Dataset, Gauge, GraphType, Paragraph, Row, Sparkline, Table, TableState, Wrap},
     Terminal,
 };
-use std::{collections::VecDeque, io, time::{Duration, Instant}};
+use std::{collections::{BTreeMap, HashMap, HashSet, VecDeque}, io, path::{Path, PathBuf}, time::{Duration, Instant, SystemTime}};
 use sysinfo::{
-    CpuRefreshKind, Disks, MemoryRefreshKind, Networks, ProcessRefreshKind, RefreshKind, System, Pid,
+    Components, CpuRefreshKind, Disks, MemoryRefreshKind, Networks, ProcessRefreshKind, ProcessStatus, RefreshKind, Signal, System, Pid, Process, ThreadKind,
 };
 
 const TICK_RATE: u64 = 1000;
 const HISTORY_LEN: usize = 100;
+const HEALTH_MODERATE: u64 = 60; // Header turns amber when CPU or memory reaches this %
+const HEALTH_CRITICAL: u64 = 90; // ...and red from here
+const SECTOR_SIZE: u64 = 512; // /proc/diskstats counts 512-byte sectors whatever the hardware uses
+const INODE_WARN: u16 = 90; // Inode usage % flagged in the Disks panel
+// Header key hints with a priority; lower numbers survive longest on narrow terminals
+const HEADER_HINTS: [(HintKey, &str, u8); 7] = [
+    (HintKey::Bound(Action::Quit), "Quit", 0),
+    (HintKey::Fixed("Tab"), "Focus", 2),
+    (HintKey::Bound(Action::Filter), "Filter", 1),
+    (HintKey::Bound(Action::Inspect), "Inspect", 1),
+    (HintKey::Bound(Action::Kill), "Kill", 1),
+    (HintKey::Bound(Action::Theme), "Theme", 3),
+    (HintKey::Fixed("C"), "Clear", 3),
+];
+const PROCESS_LIMITS: [Option<usize>; 5] = [Some(10), Some(25), Some(50), Some(100), None]; // None shows all
+const DEFAULT_PROCESS_LIMIT: usize = 2;
+const TREND_LEN: usize = 10; // Ticks of per-process CPU% the trend slope is fitted over
+const BASELINE_DIFF_ROWS: usize = 8; // Processes listed per section of the baseline diff
+const QUIET_THRESHOLD: u64 = 2; // Quiet mode redraws once CPU or memory moves this many points
+const BUSIEST_CORES: usize = 8; // Cores listed when the Cores panel is condensed
+const MEMORY_TOP_PROCESSES: usize = 5; // Processes given their own bar in the memory breakdown
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+const WATCH_EXIT_CODE: i32 = 3; // --watch: the watched process exited, as opposed to quitting by hand
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+// User settings from ~/.config/term-dash/config.toml; every field is optional
+#[derive(Deserialize)]
+#[serde(default)]
+struct Config {
+    tick_rate_ms: u64,
+    process_refresh_ms: u64, // Process list can re-sort less often than the graphs update
+    unfocused_tick_rate_ms: u64, // Tick rate while the terminal is unfocused; 0 pauses
+    commands: Vec<CommandTemplate>,
+    cpu_alert: u64, // Gauge flashes at or above this %
+    mem_alert: u64,
+    alert_command: Option<String>, // Run through `sh -c` when an alert starts firing
+    alert_command_cooldown_s: u64, // Minimum gap between runs for the same alert
+    disk_full_threshold: u64, // [L] lists only disks used at least this %
+    disk_units: DiskUnits,
+    keymap: HashMap<String, KeySpecs>, // Action name -> keys, replacing that action's defaults
+    protected: Vec<String>, // Process names [X] refuses to kill
+    watchlist: Vec<String>, // Process names always listed and highlighted
+    groups: Vec<ProcessGroup>, // Named process sets summed by the [y] view
+    profiles: Vec<LayoutProfile>, // Panel arrangements cycled by [J]
+    theme: ThemeOverrides, // Colours laid over whichever preset is active
+}
+
+// What fills the top-left area
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TopView {
+    Graphs,
+    Groups,
+    Ports,
+    Memory,
+}
+
+impl TopView {
+    fn name(&self) -> &'static str {
+        match self {
+            TopView::Graphs => "graphs",
+            TopView::Groups => "groups",
+            TopView::Ports => "ports",
+            TopView::Memory => "memory",
+        }
+    }
+}
+
+// A named panel arrangement; settings left out keep their current state when
+// the profile is applied
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+struct LayoutProfile {
+    name: String,
+    view: Option<TopView>,
+    gauges: Option<bool>,
+    portrait: Option<bool>,
+    fullscreen: Option<bool>,
+    sidebar: Option<bool>,
+    heatmap: Option<bool>,
+    gridlines: Option<bool>,
+    density: Option<Density>,
+}
+
+impl LayoutProfile {
+    // A `[[profiles]]` entry with every setting, for appending to config.toml
+    fn to_toml(&self) -> String {
+        let mut out = format!("\n[[profiles]]\nname = {}\n", toml::Value::String(self.name.clone()));
+        if let Some(view) = self.view {
+            out.push_str(&format!("view = \"{}\"\n", view.name()));
+        }
+        let flags = [
+            ("gauges", self.gauges),
+            ("portrait", self.portrait),
+            ("fullscreen", self.fullscreen),
+            ("sidebar", self.sidebar),
+            ("heatmap", self.heatmap),
+            ("gridlines", self.gridlines),
+        ];
+        for (key, value) in flags {
+            if let Some(value) = value {
+                out.push_str(&format!("{} = {}\n", key, value));
+            }
+        }
+        if let Some(density) = self.density {
+            out.push_str(&format!("density = \"{}\"\n", density.name()));
+        }
+        out
+    }
+}
+
+// Disk sizes and rates in powers of 1024 (GiB) or of 1000 (GB), to match
+// whichever tools they're compared against
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DiskUnits {
+    Binary,
+    Decimal,
+}
+
+impl DiskUnits {
+    fn base(&self) -> f64 {
+        match self {
+            DiskUnits::Binary => 1024.0,
+            DiskUnits::Decimal => 1000.0,
+        }
+    }
+
+    fn suffixes(&self) -> [&'static str; 4] {
+        match self {
+            DiskUnits::Binary => ["B", "KiB", "MiB", "GiB"],
+            DiskUnits::Decimal => ["B", "kB", "MB", "GB"],
+        }
+    }
+
+    fn format_size(&self, bytes: u64) -> String {
+        format!("{:.1} {}", bytes as f64 / self.base().powi(3), self.suffixes()[3])
+    }
+}
+
+// A keymap entry may be a single key or a list of keys
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeySpecs {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpecs {
+    fn specs(&self) -> Vec<&str> {
+        match self {
+            KeySpecs::One(spec) => vec![spec.as_str()],
+            KeySpecs::Many(specs) => specs.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+// External tool run against the selected process; `{pid}` is substituted
+#[derive(Clone, Deserialize)]
+struct CommandTemplate {
+    name: String,
+    command: String,
+}
+
+impl CommandTemplate {
+    fn new(name: &str, command: &str) -> Self {
+        Self { name: name.to_string(), command: command.to_string() }
+    }
+}
+
+// Processes whose name matches `pattern` are summed under `name`; the first
+// matching group wins
+#[derive(Clone, Deserialize)]
+struct ProcessGroup {
+    name: String,
+    #[serde(deserialize_with = "deserialize_regex")]
+    pattern: Regex,
+}
+
+fn deserialize_regex<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Regex, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(serde::de::Error::custom)
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tick_rate_ms: TICK_RATE,
+            process_refresh_ms: TICK_RATE,
+            unfocused_tick_rate_ms: 5 * TICK_RATE,
+            commands: vec![
+                CommandTemplate::new("shell", "${SHELL:-sh}"),
+                CommandTemplate::new("strace", "strace -p {pid}"),
+                CommandTemplate::new("lsof", "lsof -p {pid}"),
+            ],
+            cpu_alert: 90,
+            mem_alert: 90,
+            alert_command: None,
+            alert_command_cooldown_s: 300,
+            disk_full_threshold: 80,
+            disk_units: DiskUnits::Binary,
+            keymap: HashMap::new(),
+            protected: ["init", "systemd", "sshd", "launchd"].map(String::from).to_vec(),
+            watchlist: Vec::new(),
+            groups: Vec::new(),
+            profiles: Vec::new(),
+            theme: ThemeOverrides::default(),
+        }
+    }
+}
+
+impl Config {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("term-dash").join("config.toml"))
+    }
+
+    fn load() -> Result<Self> {
+        let Some(path) = Self::path().filter(|p| p.exists()) else {
+            return Ok(Self::default());
+        };
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    // Default bindings, with any action named in [keymap] rebound to the keys given there
+    fn keymap(&self) -> Result<HashMap<KeyCode, Action>> {
+        if let Some(name) = self.keymap.keys().find(|name| Action::from_name(name).is_none()) {
+            anyhow::bail!("unknown action `{}` in keymap", name);
+        }
+        let mut keymap = HashMap::new();
+        for action in Action::ALL {
+            if !self.keymap.contains_key(action.name()) {
+                keymap.extend(action.default_keys().iter().map(|&key| (key, action)));
+            }
+        }
+        // Explicit bindings go in last so they win over a default on the same key
+        for (name, specs) in &self.keymap {
+            let action = Action::from_name(name).expect("validated above");
+            for spec in specs.specs() {
+                let key = parse_key(spec).with_context(|| format!("unknown key `{}` for `{}` in keymap", spec, name))?;
+                keymap.insert(key, action);
+            }
+        }
+        Ok(keymap)
+    }
+}
+
+// Normal-mode actions that can be rebound from the config file
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Action {
+    Quit,
+    Next,
+    Previous,
+    Kill,
+    Inspect,
+    Filter,
+    Theme,
+}
+
+impl Action {
+    const ALL: [Action; 7] = [
+        Action::Quit,
+        Action::Next,
+        Action::Previous,
+        Action::Kill,
+        Action::Inspect,
+        Action::Filter,
+        Action::Theme,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Next => "next",
+            Action::Previous => "prev",
+            Action::Kill => "kill",
+            Action::Inspect => "inspect",
+            Action::Filter => "filter",
+            Action::Theme => "theme",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn default_keys(&self) -> &'static [KeyCode] {
+        match self {
+            Action::Quit => &[KeyCode::Char('q'), KeyCode::Esc],
+            Action::Next => &[KeyCode::Down, KeyCode::Char('j')],
+            Action::Previous => &[KeyCode::Up, KeyCode::Char('k')],
+            Action::Kill => &[KeyCode::Char('x'), KeyCode::Delete],
+            Action::Inspect => &[KeyCode::Enter],
+            Action::Filter => &[KeyCode::Char('/')],
+            Action::Theme => &[KeyCode::Char('t')],
+        }
+    }
+}
+
+// Key shown for a header hint; rebindable actions show whatever the keymap gives them
+enum HintKey {
+    Fixed(&'static str),
+    Bound(Action),
+}
 
 #[derive(Clone, Copy, PartialEq)]
 enum InputMode {
     Normal,
     Editing,
     Details, // New mode for Process Inspector
+    Affinity, // Editing the inspected process's CPU affinity
+    PidEntry, // Typing a PID to jump to
+    TickRateEntry, // Typing a refresh interval in milliseconds
+    ProfileName, // Naming the current layout to save it as a profile
+    Commands, // Picking a command template to run against a process
+    Compare, // Two marked processes side by side
+    Baseline, // What changed since the baseline snapshot
+}
+
+// PID, name, CPU% and memory of every process at one moment
+type ProcessSnapshot = Vec<(Pid, String, f32, u64)>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum SortBy {
+    Cpu,
+    Memory,
+    Trend, // Slope of recent CPU%, so processes ramping up surface before they peak
+    Connections, // Open network connections, to spot services leaking sockets (Linux only)
+}
+
+// What the process table's name column shows
+#[derive(Clone, Copy, PartialEq)]
+enum NameDisplay {
+    Name,
+    Path,
+    Command,
+}
+
+impl NameDisplay {
+    fn next(&self) -> Self {
+        match self {
+            NameDisplay::Name => NameDisplay::Path,
+            NameDisplay::Path => NameDisplay::Command,
+            NameDisplay::Command => NameDisplay::Name,
+        }
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            NameDisplay::Name => "Name",
+            NameDisplay::Path => "Path",
+            NameDisplay::Command => "Command",
+        }
+    }
+}
+
+// Process table spacing: Compact fits one process per line, Comfortable leaves a
+// blank line under each row and wider gaps between columns
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Density {
+    Compact,
+    Comfortable,
+}
+
+impl Density {
+    fn next(&self) -> Self {
+        match self {
+            Density::Compact => Density::Comfortable,
+            Density::Comfortable => Density::Compact,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Density::Compact => "compact",
+            Density::Comfortable => "comfortable",
+        }
+    }
+
+    fn row_margin(&self) -> u16 {
+        match self {
+            Density::Compact => 0,
+            Density::Comfortable => 1,
+        }
+    }
+
+    fn column_spacing(&self) -> u16 {
+        match self {
+            Density::Compact => 1,
+            Density::Comfortable => 2,
+        }
+    }
+}
+
+// Whether the Name column shows the kernel's short name (truncated to 15 characters
+// on Linux) or the executable's full file name. Auto picks the full name when it
+// fits the column.
+#[derive(Clone, Copy, PartialEq)]
+enum NameLength {
+    Auto,
+    Short,
+    Long,
+}
+
+impl NameLength {
+    fn next(&self) -> Self {
+        match self {
+            NameLength::Auto => NameLength::Short,
+            NameLength::Short => NameLength::Long,
+            NameLength::Long => NameLength::Auto,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            NameLength::Auto => "auto (full when they fit)",
+            NameLength::Short => "short",
+            NameLength::Long => "full",
+        }
+    }
+}
+
+// What the CPU graph plots
+#[derive(Clone, Copy, PartialEq)]
+enum CpuGraph {
+    Total,
+    PerCore, // Stacked per-core chart
+    Breakdown, // Stacked user/system/iowait/steal chart (Linux or --scrape)
+}
+
+impl CpuGraph {
+    fn next(&self) -> Self {
+        match self {
+            CpuGraph::Total => CpuGraph::PerCore,
+            CpuGraph::PerCore => CpuGraph::Breakdown,
+            CpuGraph::Breakdown => CpuGraph::Total,
+        }
+    }
+}
+
+// How many seconds each sparkline column covers. Coarser buckets plot the average
+// of a longer raw buffer, trading spikes for trends.
+#[derive(Clone, Copy, PartialEq)]
+enum Granularity {
+    Tick,
+    FiveSeconds,
+    ThirtySeconds,
+    Minute,
+}
+
+impl Granularity {
+    fn next(&self) -> Self {
+        match self {
+            Granularity::Tick => Granularity::FiveSeconds,
+            Granularity::FiveSeconds => Granularity::ThirtySeconds,
+            Granularity::ThirtySeconds => Granularity::Minute,
+            Granularity::Minute => Granularity::Tick,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Granularity::Tick => "per tick",
+            Granularity::FiveSeconds => "5s",
+            Granularity::ThirtySeconds => "30s",
+            Granularity::Minute => "1m",
+        }
+    }
+
+    // Raw samples per column at the given tick rate; never less than one
+    fn samples_per_bucket(&self, tick_rate: Duration) -> usize {
+        let seconds = match self {
+            Granularity::Tick => return 1,
+            Granularity::FiveSeconds => 5,
+            Granularity::ThirtySeconds => 30,
+            Granularity::Minute => 60,
+        };
+        (Duration::from_secs(seconds).as_millis() / tick_rate.as_millis().max(1)).max(1) as usize
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FocusedPanel {
+    Processes,
+    Disks,
+    Network,
+    Cores,
+}
+
+impl FocusedPanel {
+    fn next(&self) -> Self {
+        match self {
+            FocusedPanel::Processes => FocusedPanel::Disks,
+            FocusedPanel::Disks => FocusedPanel::Network,
+            FocusedPanel::Network => FocusedPanel::Cores,
+            FocusedPanel::Cores => FocusedPanel::Processes,
+        }
+    }
+
+    fn previous(&self) -> Self {
+        match self {
+            FocusedPanel::Processes => FocusedPanel::Cores,
+            FocusedPanel::Disks => FocusedPanel::Processes,
+            FocusedPanel::Network => FocusedPanel::Disks,
+            FocusedPanel::Cores => FocusedPanel::Network,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -32,6 +541,7 @@ enum ThemePreset {
     Default,
     Cyberpunk,
     Matrix,
+    Light, // For light-background terminals
 }
 
 impl ThemePreset {
@@ -39,7 +549,17 @@ impl ThemePreset {
         match self {
             ThemePreset::Default => ThemePreset::Cyberpunk,
             ThemePreset::Cyberpunk => ThemePreset::Matrix,
-            ThemePreset::Matrix => ThemePreset::Default,
+            ThemePreset::Matrix => ThemePreset::Light,
+            ThemePreset::Light => ThemePreset::Default,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ThemePreset::Default => "Default",
+            ThemePreset::Cyberpunk => "Cyberpunk",
+            ThemePreset::Matrix => "Matrix",
+            ThemePreset::Light => "Light",
         }
     }
 
@@ -87,7 +607,91 @@ impl ThemePreset {
                 gauge_cpu_low: Color::DarkGray,
                 gauge_mem: Color::Green,
             },
+            // Darker shades that stay readable on white
+            ThemePreset::Light => Theme {
+                bg: Color::Reset,
+                border: Color::Blue,
+                text: Color::Black,
+                highlight_fg: Color::White,
+                highlight_bg: Color::Blue,
+                graph_cpu: Color::Indexed(28),
+                graph_mem: Color::Indexed(90),
+                graph_net_rx: Color::Indexed(130),
+                graph_net_tx: Color::Indexed(25),
+                gauge_cpu_high: Color::Indexed(124),
+                gauge_cpu_low: Color::Indexed(28),
+                gauge_mem: Color::Indexed(90),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HealthLevel {
+    Good,
+    Moderate,
+    Critical,
+}
+
+impl HealthLevel {
+    // The worst of CPU and memory decides the overall health
+    fn from_usage(cpu: u64, mem: u64) -> Self {
+        let score = cpu.max(mem);
+        if score >= HEALTH_CRITICAL {
+            HealthLevel::Critical
+        } else if score >= HEALTH_MODERATE {
+            HealthLevel::Moderate
+        } else {
+            HealthLevel::Good
+        }
+    }
+
+    // Dark 256-colour tints so the header text stays readable
+    fn color(&self) -> Color {
+        match self {
+            HealthLevel::Good => Color::Indexed(22),
+            HealthLevel::Moderate => Color::Indexed(94),
+            HealthLevel::Critical => Color::Indexed(52),
+        }
+    }
+}
+
+// Threshold alert for one metric; an acknowledgement lasts until the metric
+// falls back below the threshold, so the next crossing alerts again
+struct Alert {
+    threshold: u64,
+    firing: bool,
+    acknowledged: bool,
+    command_run: Option<Instant>, // Last time alert_command ran for this alert
+}
+
+impl Alert {
+    fn new(threshold: u64) -> Self {
+        Self { threshold, firing: false, acknowledged: false, command_run: None }
+    }
+
+    // Whether the alert just started firing
+    fn update(&mut self, value: u64) -> bool {
+        let was_firing = self.firing;
+        self.firing = value >= self.threshold;
+        if !self.firing {
+            self.acknowledged = false;
         }
+        self.firing && !was_firing
+    }
+
+    // Rate-limited so a metric hovering around its threshold doesn't run the
+    // command on every crossing
+    fn command_due(&mut self, cooldown: Duration) -> bool {
+        if self.command_run.is_some_and(|run| run.elapsed() < cooldown) {
+            return false;
+        }
+        self.command_run = Some(Instant::now());
+        true
+    }
+
+    fn flashing(&self) -> bool {
+        self.firing && !self.acknowledged
     }
 }
 
@@ -106,342 +710,4272 @@ struct Theme {
     gauge_mem: Color,
 }
 
-struct App {
-    system: System,
-    networks: Networks,
-    disks: Disks,
-    cpu_history: VecDeque<u64>,
-    mem_history: VecDeque<u64>,
-    net_rx_history: VecDeque<u64>,
-    net_tx_history: VecDeque<u64>,
-    should_quit: bool,
-    // Process Interaction
-    process_state: TableState,
-    processes: Vec<(Pid, String, f32, u64)>, // Cache for list
-    input_mode: InputMode,
-    search_query: String,
-    selected_pid: Option<Pid>, // Track which process is inspected
-    current_theme: ThemePreset,
+impl Theme {
+    // A `[theme]` table of colour names and `#RRGGBB` values, one line per field
+    fn to_toml(&self) -> String {
+        let fields = [
+            ("bg", self.bg),
+            ("border", self.border),
+            ("text", self.text),
+            ("highlight_fg", self.highlight_fg),
+            ("highlight_bg", self.highlight_bg),
+            ("graph_cpu", self.graph_cpu),
+            ("graph_mem", self.graph_mem),
+            ("graph_net_rx", self.graph_net_rx),
+            ("graph_net_tx", self.graph_net_tx),
+            ("gauge_cpu_high", self.gauge_cpu_high),
+            ("gauge_cpu_low", self.gauge_cpu_low),
+            ("gauge_mem", self.gauge_mem),
+        ];
+        let mut out = String::from("[theme]\n");
+        for (key, color) in fields {
+            out.push_str(&format!("{} = \"{}\"\n", key, color));
+        }
+        out
+    }
 }
 
-impl App {
-    fn new() -> Self {
-        let r = RefreshKind::new()
-            .with_cpu(CpuRefreshKind::everything())
-            .with_memory(MemoryRefreshKind::everything())
-            .with_processes(ProcessRefreshKind::everything());
+// A `[theme]` table from config.toml; each colour given replaces the preset's
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeOverrides {
+    #[serde(deserialize_with = "deserialize_color")]
+    bg: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    border: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    text: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    highlight_fg: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    highlight_bg: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    graph_cpu: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    graph_mem: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    graph_net_rx: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    graph_net_tx: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    gauge_cpu_high: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    gauge_cpu_low: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    gauge_mem: Option<Color>,
+}
 
-        let mut system = System::new_with_specifics(r);
-        let networks = Networks::new_with_refreshed_list();
-        let disks = Disks::new_with_refreshed_list();
-        system.refresh_all();
-        
-        let mut process_state = TableState::default();
-        process_state.select(Some(0));
+// Colour names like "lightblue", "#RRGGBB" or a 0-255 palette index
+fn deserialize_color<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Color>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(|_| serde::de::Error::custom(format!("unknown colour \"{}\"", value)))
+}
 
-        Self {
-            system,
-            networks,
-            disks,
-            cpu_history: VecDeque::from(vec![0; HISTORY_LEN]),
-            mem_history: VecDeque::from(vec![0; HISTORY_LEN]),
-            net_rx_history: VecDeque::from(vec![0; HISTORY_LEN]),
-            net_tx_history: VecDeque::from(vec![0; HISTORY_LEN]),
-            should_quit: false,
-            process_state,
-            processes: Vec::new(),
-            input_mode: InputMode::Normal,
-            search_query: String::new(),
-            selected_pid: None,
-            current_theme: ThemePreset::Default,
+impl ThemeOverrides {
+    fn apply(&self, theme: Theme) -> Theme {
+        Theme {
+            bg: self.bg.unwrap_or(theme.bg),
+            border: self.border.unwrap_or(theme.border),
+            text: self.text.unwrap_or(theme.text),
+            highlight_fg: self.highlight_fg.unwrap_or(theme.highlight_fg),
+            highlight_bg: self.highlight_bg.unwrap_or(theme.highlight_bg),
+            graph_cpu: self.graph_cpu.unwrap_or(theme.graph_cpu),
+            graph_mem: self.graph_mem.unwrap_or(theme.graph_mem),
+            graph_net_rx: self.graph_net_rx.unwrap_or(theme.graph_net_rx),
+            graph_net_tx: self.graph_net_tx.unwrap_or(theme.graph_net_tx),
+            gauge_cpu_high: self.gauge_cpu_high.unwrap_or(theme.gauge_cpu_high),
+            gauge_cpu_low: self.gauge_cpu_low.unwrap_or(theme.gauge_cpu_low),
+            gauge_mem: self.gauge_mem.unwrap_or(theme.gauge_mem),
         }
     }
+}
 
-    fn on_tick(&mut self) {
-        self.system.refresh_all();
-        self.networks.refresh(); 
-        self.disks.refresh_list();
+// One thread of the expanded process (Linux only)
+struct ThreadInfo {
+    tid: u32,
+    name: String,
+    cpu: f32,
+    cpu_time: Duration,
+}
 
-        // Update History
-        let cpu_usage = self.system.global_cpu_info().cpu_usage() as u64;
-        self.cpu_history.pop_front();
-        self.cpu_history.push_back(cpu_usage);
+#[derive(Clone)]
+struct ProcessInfo {
+    pid: Pid,
+    name: String,
+    cpu: f32,
+    cpu_trend: f32, // CPU% change per tick, see `slope`
+    cpu_time: Option<Duration>, // Total CPU time used, only filled while shown
+    mem: u64,
+    mem_percent: f32, // Of total RAM
+    start_time: u64, // Unix timestamp
+    status: ProcessStatus,
+    exe: String,
+    cmd: String,
+}
 
-        let total_mem = self.system.total_memory();
-        let used_mem = self.system.used_memory();
-        let mem_percent = if total_mem > 0 {
-            (used_mem as f64 / total_mem as f64 * 100.0) as u64
-        } else {
-            0
-        };
-        self.mem_history.pop_front();
-        self.mem_history.push_back(mem_percent);
+struct InterfaceInfo {
+    name: String,
+    rx: u64, // Bytes since the previous refresh
+    tx: u64,
+    rx_rate: f64, // Bytes/s over the measured interval, set by record_interfaces
+    tx_rate: f64,
+    up: bool,
+    mac: String,
+    ips: Vec<String>,
+}
+
+#[derive(Clone)]
+struct DiskInfo {
+    mount: PathBuf,
+    device: String, // Kernel block device name as in /proc/diskstats; empty when unknown
+    total: u64,
+    available: u64,
+    stale: bool, // Last good reading, kept while the disk reports no size
+}
+
+// Cumulative CPU time by category, in whatever unit the source counts
+#[derive(Clone, Copy, Default)]
+struct CpuTimes {
+    user: f64, // Includes nice
+    system: f64, // Includes irq and softirq
+    iowait: f64,
+    steal: f64,
+    total: f64,
+}
+
+impl CpuTimes {
+    // Percentage of the time since `prev` spent in each category
+    fn breakdown_since(&self, prev: &CpuTimes) -> CpuBreakdown {
+        let elapsed = self.total - prev.total;
+        let percent = |now: f64, before: f64| if elapsed > 0.0 { ((now - before) / elapsed * 100.0).clamp(0.0, 100.0) } else { 0.0 };
+        CpuBreakdown {
+            user: percent(self.user, prev.user),
+            system: percent(self.system, prev.system),
+            iowait: percent(self.iowait, prev.iowait),
+            steal: percent(self.steal, prev.steal),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct CpuBreakdown {
+    user: f64,
+    system: f64,
+    iowait: f64,
+    steal: f64,
+}
+
+impl CpuBreakdown {
+    fn values(&self) -> [f64; 4] {
+        [self.user, self.system, self.iowait, self.steal]
+    }
+}
+
+struct ListeningPort {
+    proto: &'static str,
+    address: String,
+    port: u16,
+    pid: Option<Pid>,
+}
+
+// Summed usage of the processes in one configured group
+struct GroupUsage {
+    name: String,
+    processes: usize,
+    cpu: f32,
+    mem: u64,
+}
+
+// A Prometheus node-exporter scraped in place of the local machine
+struct RemoteSource {
+    url: String,
+    agent: ureq::Agent,
+    host: String, // The node's hostname once known, until then the URL
+    prev: Option<NodeMetrics>, // Last scrape's counters, for per-tick deltas
+}
+
+impl RemoteSource {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(2)).build(),
+            host: url.to_string(),
+            prev: None,
+        }
+    }
+
+    fn scrape(&self) -> Result<NodeMetrics> {
+        let body = self.agent.get(&self.url).call()?.into_string()?;
+        Ok(NodeMetrics::parse(&body))
+    }
+}
+
+// The node-exporter metrics term-dash can show, as raw counters and gauges
+#[derive(Default)]
+struct NodeMetrics {
+    nodename: Option<String>,
+    cpus: BTreeMap<usize, (f64, f64)>, // CPU index -> (idle seconds, total seconds)
+    cpu_times: CpuTimes, // Summed over all CPUs
+    mem_total: u64,
+    mem_available: u64,
+    swap_total: u64,
+    swap_free: u64,
+    filesystems: BTreeMap<String, NodeFilesystem>, // By mount point
+    interfaces: BTreeMap<String, NodeInterface>, // By device
+    context_switches: Option<u64>,
+    max_temperature: Option<f32>, // Hottest hwmon or thermal zone sensor, °C
+}
+
+#[derive(Default)]
+struct NodeFilesystem {
+    total: u64,
+    available: u64,
+    files: u64,
+    files_free: u64,
+}
+
+#[derive(Default)]
+struct NodeInterface {
+    rx: u64, // Bytes since boot
+    tx: u64,
+    up: Option<bool>,
+    mac: String,
+}
+
+impl NodeMetrics {
+    fn parse(text: &str) -> Self {
+        let mut metrics = Self::default();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let Some((name, labels, value)) = parse_sample(line) else { continue };
+            let label = |key: &str| labels.get(key).cloned().unwrap_or_default();
+            match name {
+                "node_cpu_seconds_total" => {
+                    let Ok(cpu) = label("cpu").parse() else { continue };
+                    let (idle, total) = metrics.cpus.entry(cpu).or_default();
+                    *total += value;
+                    // Time waiting on I/O is idle from the CPU's point of view
+                    let mode = label("mode");
+                    if matches!(mode.as_str(), "idle" | "iowait") {
+                        *idle += value;
+                    }
+                    let times = &mut metrics.cpu_times;
+                    times.total += value;
+                    match mode.as_str() {
+                        "user" | "nice" => times.user += value,
+                        "system" | "irq" | "softirq" => times.system += value,
+                        "iowait" => times.iowait += value,
+                        "steal" => times.steal += value,
+                        _ => {}
+                    }
+                }
+                "node_memory_MemTotal_bytes" => metrics.mem_total = value as u64,
+                "node_memory_MemAvailable_bytes" => metrics.mem_available = value as u64,
+                "node_memory_SwapTotal_bytes" => metrics.swap_total = value as u64,
+                "node_memory_SwapFree_bytes" => metrics.swap_free = value as u64,
+                "node_filesystem_size_bytes" => metrics.filesystems.entry(label("mountpoint")).or_default().total = value as u64,
+                "node_filesystem_avail_bytes" => metrics.filesystems.entry(label("mountpoint")).or_default().available = value as u64,
+                "node_filesystem_files" => metrics.filesystems.entry(label("mountpoint")).or_default().files = value as u64,
+                "node_filesystem_files_free" => metrics.filesystems.entry(label("mountpoint")).or_default().files_free = value as u64,
+                "node_network_receive_bytes_total" => metrics.interfaces.entry(label("device")).or_default().rx = value as u64,
+                "node_network_transmit_bytes_total" => metrics.interfaces.entry(label("device")).or_default().tx = value as u64,
+                "node_network_info" => {
+                    let iface = metrics.interfaces.entry(label("device")).or_default();
+                    iface.mac = label("address");
+                    iface.up = Some(label("operstate") == "up");
+                }
+                "node_context_switches_total" => metrics.context_switches = Some(value as u64),
+                "node_hwmon_temp_celsius" | "node_thermal_zone_temp" if value.is_finite() => {
+                    let celsius = value as f32;
+                    metrics.max_temperature = Some(metrics.max_temperature.map_or(celsius, |max| max.max(celsius)));
+                }
+                "node_uname_info" => metrics.nodename = Some(label("nodename")),
+                _ => {}
+            }
+        }
+        metrics
+    }
+}
+
+// Command-line options
+struct Args {
+    scrape: Option<String>, // node-exporter metrics URL
+    anonymize: bool,
+    stress: bool, // Undocumented: generate load so the graphs have something to show
+    watch: Option<Pid>, // Show this process's details and quit when it exits
+    check: bool, // Print a one-line summary and exit instead of starting the TUI
+    warn: Option<u64>, // --check thresholds, in %
+    crit: Option<u64>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = Self { scrape: None, anonymize: false, stress: false, watch: None, check: false, warn: None, crit: None };
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--scrape" => args.scrape = Some(iter.next().context("--scrape needs a URL, e.g. http://host:9100/metrics")?),
+                "--anonymize" => args.anonymize = true,
+                "--stress" => args.stress = true,
+                "--check" => args.check = true,
+                "--warn" | "--crit" => {
+                    let value = iter.next().with_context(|| format!("{} needs a percentage", arg))?;
+                    let percent = Some(value.parse().with_context(|| format!("invalid percentage `{}`", value))?);
+                    if arg == "--warn" { args.warn = percent } else { args.crit = percent }
+                }
+                "--watch" => {
+                    let pid = iter.next().context("--watch needs a PID")?;
+                    args.watch = Some(Pid::from_u32(pid.parse().with_context(|| format!("invalid PID `{}`", pid))?));
+                }
+                other => anyhow::bail!("unknown argument `{}`", other),
+            }
+        }
+        if (args.warn.is_some() || args.crit.is_some()) && !args.check {
+            anyhow::bail!("--warn and --crit only apply to --check");
+        }
+        if args.watch.is_some() && args.scrape.is_some() {
+            anyhow::bail!("--watch needs local processes and can't be combined with --scrape");
+        }
+        Ok(args)
+    }
+}
+
+struct App {
+    system: System,
+    networks: Networks,
+    disks: Disks,
+    components: Components,
+    disk_info: Vec<DiskInfo>, // Filled from `disks`, or from the scrape in remote mode
+    disk_io: HashMap<String, (u64, u64)>, // Read/write bytes per second by block device (Linux only)
+    disk_sectors: HashMap<String, (u64, u64)>, // Previous sectors read/written by block device
+    disk_io_sampled: Option<Instant>,
+    swap_percent: u64,
+    // Opened on first copy and kept, as on X11 the copied text lives only as long as its owner
+    clipboard: Option<arboard::Clipboard>,
+    mem_stale: bool, // Total memory read as 0, so the last good sample was repeated
+    remote: Option<RemoteSource>, // Set by --scrape; replaces every local source
+    cpu_history: VecDeque<u64>,
+    core_history: Vec<VecDeque<u64>>, // Per logical CPU, for the stacked graph
+    mem_history: VecDeque<u64>,
+    net_rx_history: VecDeque<u64>, // Bytes per second
+    net_tx_history: VecDeque<u64>,
+    net_rx_total_history: VecDeque<u64>, // Bytes since start or the last clear
+    net_tx_total_history: VecDeque<u64>,
+    net_sampled: Option<Instant>,
+    net_cumulative: bool, // Sparklines plot the running totals instead of the rates
+    ctxt_history: VecDeque<u64>, // Context switches per second (Linux only)
+    ctxt_sampled: Option<Instant>,
+    sample_times: VecDeque<SystemTime>, // Wall-clock time of each CPU/memory sample, newest last
+    real_time_axis: bool, // Plot CPU and memory against wall-clock time instead of one column per tick
+    temp_history: VecDeque<u64>, // Hottest sensor, °C
+    temperature: Option<f32>, // None when there are no readable sensors
+    prev_ctxt: Option<u64>,
+    should_quit: bool,
+    health: HealthLevel,
+    zombie_count: usize,
+    // Process Interaction
+    process_state: TableState,
+    processes: Vec<ProcessInfo>, // Cache for list
+    known_pids: HashSet<Pid>, // Every PID seen on the previous tick
+    cpu_trends: HashMap<Pid, VecDeque<f32>>, // Last TREND_LEN CPU% samples per process
+    killed_pids: HashSet<Pid>, // Signalled from [X]; struck through until they drop off
+    new_pids: HashSet<Pid>, // Appeared since the previous tick; drawn green
+    vanished_pids: HashSet<Pid>, // Gone since the previous tick; kept one more tick in red
+    input_mode: InputMode,
+    search_query: String,
+    search_case_sensitive: bool, // Name and path: filters match case exactly
+    search_filter: Option<ProcessFilter>, // Parsed from search_query when it changes, not every tick
+    selected_pid: Option<Pid>, // Track which process is inspected
+    details_wrap: bool, // Wrap the command line in the details popup rather than scroll it
+    details_scroll: usize, // Characters of the command line scrolled off to the left
+    watch_pid: Option<Pid>, // From --watch; the list is filtered to it
+    compare_mark: Option<Pid>, // First process picked for comparison
+    compare_pids: Option<(Pid, Pid)>,
+    baseline: Option<(Instant, ProcessSnapshot)>,
+    baseline_current: Option<ProcessSnapshot>, // Taken when the diff is opened
+    watched_exited: bool,
+    current_theme: ThemePreset,
+    theme_overrides: ThemeOverrides,
+    // Panel Navigation
+    focused_panel: FocusedPanel,
+    disk_state: TableState,
+    only_full_disks: bool,
+    disk_full_threshold: u64,
+    disk_units: DiskUnits,
+    network_state: TableState,
+    core_state: TableState,
+    interfaces: Vec<InterfaceInfo>, // Cache for list
+    inode_usage: HashMap<PathBuf, u16>, // Inode usage % per mount point
+    disk_history: HashMap<PathBuf, VecDeque<u64>>, // Used % per mount point
+    core_topology: Option<Vec<usize>>, // Physical core of each logical CPU, where known
+    affinity_input: String,
+    pid_input: String,
+    tick_rate_input: String,
+    status: Option<(String, Instant)>, // Transient message shown in the header
+    spinner_frame: usize,
+    refresh_duration: Duration, // How long the last on_tick refresh took
+    show_filter_bar: bool,
+    show_gauges: bool, // Hidden gauges give their row to the graphs and processes
+    portrait: bool, // Stack the side-by-side panels for tall, narrow terminals
+    process_fullscreen: bool,
+    show_cpu_time: bool, // Total CPU time used instead of current CPU% (Linux only)
+    expanded_pid: Option<Pid>, // Process whose threads are listed under it (Linux only)
+    threads: Vec<ThreadInfo>,
+    thread_sampled: Option<Instant>,
+    anonymize: bool, // Generic process labels and no command lines, for screen sharing
+    process_aliases: HashMap<Pid, usize>, // Stable "process-N" number per PID
+    next_alias: usize, // Number the next anonymised process gets, as in `process-N`
+    details_sidebar: bool, // Details beside the process list instead of in a popup
+    snapshot_requested: bool, // Saved by the main loop after the next draw
+    quiet: bool, // Only redraw on input or a significant change, for slow links
+    auto_follow: bool, // Keep the busiest process selected each tick
+    needs_redraw: bool,
+    drawn: (u64, u64, HashMap<Pid, f32>), // CPU%, memory% and each listed process's CPU% on screen, for quiet mode
+    show_ports: bool,
+    listening_ports: Vec<ListeningPort>, // Only refreshed while the ports view is shown
+    show_groups: bool,
+    process_groups: Vec<ProcessGroup>,
+    profiles: Vec<LayoutProfile>,
+    profile_index: Option<usize>, // Last profile applied, so [J] moves on to the next
+    profile_name_input: String,
+    group_usage: Vec<GroupUsage>, // Only refreshed while the groups view is shown
+    show_memory_breakdown: bool,
+    gridded_graphs: bool, // CPU and memory as line charts over 25/50/75% rules
+    memory_breakdown: Vec<(String, u64)>, // Label and bytes; only refreshed while shown
+    show_start_column: bool,
+    show_table_header: bool, // Hidden, the header's line goes to another process row
+    density: Density,
+    mark_abnormal: bool, // Dim stopped and strike through zombie processes
+    show_watchlist: bool,
+    freeze_list: bool,
+    sort_by: SortBy,
+    name_display: NameDisplay,
+    name_length: NameLength,
+    process_limit: usize, // Index into PROCESS_LIMITS
+    unlisted: Option<(usize, f32, u64)>, // Count, CPU% and memory of processes cut off by the limit
+    invert_gauges: bool, // Show free instead of used for memory, swap and disk
+    cpu_graph: CpuGraph,
+    granularity: Granularity,
+    raw_history_len: usize, // Samples kept per sparkline, enough for HISTORY_LEN one-minute buckets
+    cpu_breakdown_history: VecDeque<CpuBreakdown>, // Linux or --scrape only
+    prev_cpu_times: Option<CpuTimes>,
+    core_heatmap: bool,
+    busiest_cores: bool, // List only the BUSIEST_CORES busiest cores, summarising the rest
+    cpu_gauge_max_core: bool, // CPU gauge shows the busiest core instead of the average
+    show_core_temps: bool,
+    group_digits: bool, // Thousands separators in PIDs and raw counts
+    core_temps: HashMap<usize, f32>, // Sensor labelled "Core N" -> °C, keyed by N
+    cpu_alert: Alert,
+    mem_alert: Alert,
+    alert_command: Option<String>,
+    alert_command_cooldown: Duration,
+    show_process_net: bool,
+    process_net: HashMap<Pid, (u64, u64)>, // RX/TX bytes per second of each listed process's net namespace, not the process's own
+    netns_sampled: Option<Instant>,
+    connection_counts: HashMap<Pid, usize>, // Only refreshed while sorting by connections
+    show_containers: bool,
+    containers: HashMap<Pid, Option<String>>, // Container name or short ID; None on the host
+    show_swap_column: bool,
+    process_swap: HashMap<Pid, u64>, // Swapped-out bytes of each listed process, while the column shows
+    netns_totals: HashMap<u64, (u64, u64)>, // Previous counters per namespace inode
+    tick_rate: Duration,
+    unfocused_tick_rate: Option<Duration>, // None pauses refreshing while unfocused
+    terminal_focused: bool, // Stays true on terminals that don't report focus
+    process_interval: Duration,
+    last_process_refresh: Option<Instant>,
+    command_templates: Vec<CommandTemplate>,
+    protected_processes: Vec<String>,
+    watchlist: Vec<String>,
+    keymap: HashMap<KeyCode, Action>,
+    command_target: Option<Pid>,
+    pending_command: Option<(String, Pid)>, // Run by the main loop, which owns the terminal
+}
+
+impl App {
+    fn new(config: &Config, keymap: HashMap<KeyCode, Action>, args: &Args) -> Self {
+        let r = RefreshKind::new()
+            .with_cpu(CpuRefreshKind::everything())
+            .with_memory(MemoryRefreshKind::everything())
+            .with_processes(ProcessRefreshKind::everything());
+
+        let mut system = System::new_with_specifics(r);
+        let networks = Networks::new_with_refreshed_list();
+        let disks = Disks::new_with_refreshed_list();
+        let components = Components::new_with_refreshed_list();
+        system.refresh_all();
+        let core_count = system.cpus().len();
+        // The local machine's hyperthread grouping says nothing about a scraped host's
+        let core_topology = if args.scrape.is_some() { None } else { physical_core_ids(core_count) };
+        
+        let mut process_state = TableState::default();
+        process_state.select(Some(0));
+
+        let mut disk_state = TableState::default();
+        disk_state.select(Some(0));
+        let mut network_state = TableState::default();
+        network_state.select(Some(0));
+        let mut core_state = TableState::default();
+        core_state.select(Some(0));
+
+        Self {
+            system,
+            networks,
+            disks,
+            components,
+            disk_info: Vec::new(),
+            disk_io: HashMap::new(),
+            disk_sectors: HashMap::new(),
+            disk_io_sampled: None,
+            swap_percent: 0,
+            clipboard: None,
+            mem_stale: false,
+            remote: args.scrape.as_deref().map(RemoteSource::new),
+            cpu_history: VecDeque::from(vec![0; HISTORY_LEN]),
+            core_history: vec![VecDeque::from(vec![0; HISTORY_LEN]); core_count],
+            mem_history: VecDeque::from(vec![0; HISTORY_LEN]),
+            net_rx_history: VecDeque::from(vec![0; HISTORY_LEN]),
+            net_tx_history: VecDeque::from(vec![0; HISTORY_LEN]),
+            net_rx_total_history: VecDeque::from(vec![0; HISTORY_LEN]),
+            net_tx_total_history: VecDeque::from(vec![0; HISTORY_LEN]),
+            net_sampled: None,
+            net_cumulative: false,
+            ctxt_history: VecDeque::from(vec![0; HISTORY_LEN]),
+            temp_history: VecDeque::from(vec![0; HISTORY_LEN]),
+            temperature: None,
+            prev_ctxt: None,
+            ctxt_sampled: None,
+            sample_times: VecDeque::new(),
+            real_time_axis: false,
+            should_quit: false,
+            health: HealthLevel::Good,
+            zombie_count: 0,
+            process_state,
+            processes: Vec::new(),
+            known_pids: HashSet::new(),
+            cpu_trends: HashMap::new(),
+            killed_pids: HashSet::new(),
+            new_pids: HashSet::new(),
+            vanished_pids: HashSet::new(),
+            input_mode: if args.watch.is_some() { InputMode::Details } else { InputMode::Normal },
+            search_query: String::new(),
+            search_case_sensitive: false,
+            search_filter: None,
+            details_wrap: true,
+            details_scroll: 0,
+            selected_pid: args.watch,
+            watch_pid: args.watch,
+            compare_mark: None,
+            compare_pids: None,
+            baseline: None,
+            baseline_current: None,
+            watched_exited: false,
+            current_theme: ThemePreset::Default,
+            theme_overrides: config.theme.clone(),
+            focused_panel: FocusedPanel::Processes,
+            disk_state,
+            only_full_disks: false,
+            disk_full_threshold: config.disk_full_threshold,
+            disk_units: config.disk_units,
+            network_state,
+            core_state,
+            interfaces: Vec::new(),
+            inode_usage: HashMap::new(),
+            disk_history: HashMap::new(),
+            core_topology,
+            affinity_input: String::new(),
+            pid_input: String::new(),
+            tick_rate_input: String::new(),
+            status: None,
+            spinner_frame: 0,
+            refresh_duration: Duration::ZERO,
+            show_filter_bar: true,
+            show_gauges: true,
+            portrait: false,
+            process_fullscreen: false,
+            show_cpu_time: false,
+            expanded_pid: None,
+            threads: Vec::new(),
+            thread_sampled: None,
+            anonymize: args.anonymize,
+            process_aliases: HashMap::new(),
+            next_alias: 1,
+            details_sidebar: false,
+            snapshot_requested: false,
+            quiet: false,
+            auto_follow: false,
+            needs_redraw: true,
+            drawn: (0, 0, HashMap::new()),
+            show_ports: false,
+            show_groups: false,
+            show_memory_breakdown: false,
+            gridded_graphs: false,
+            memory_breakdown: Vec::new(),
+            process_groups: config.groups.clone(),
+            profiles: config.profiles.clone(),
+            profile_index: None,
+            profile_name_input: String::new(),
+            group_usage: Vec::new(),
+            listening_ports: Vec::new(),
+            show_start_column: false,
+            show_table_header: true,
+            density: Density::Compact,
+            mark_abnormal: true,
+            show_watchlist: true,
+            freeze_list: false,
+            sort_by: SortBy::Cpu,
+            name_display: NameDisplay::Name,
+            name_length: NameLength::Auto,
+            process_limit: DEFAULT_PROCESS_LIMIT,
+            unlisted: None,
+            invert_gauges: false,
+            cpu_graph: CpuGraph::Total,
+            granularity: Granularity::Tick,
+            raw_history_len: HISTORY_LEN * Granularity::Minute.samples_per_bucket(Duration::from_millis(config.tick_rate_ms.max(1))),
+            cpu_breakdown_history: VecDeque::from(vec![CpuBreakdown::default(); HISTORY_LEN]),
+            prev_cpu_times: None,
+            core_heatmap: false,
+            busiest_cores: false,
+            cpu_gauge_max_core: false,
+            show_core_temps: false,
+            group_digits: false,
+            core_temps: HashMap::new(),
+            cpu_alert: Alert::new(config.cpu_alert),
+            mem_alert: Alert::new(config.mem_alert),
+            alert_command: config.alert_command.clone(),
+            alert_command_cooldown: Duration::from_secs(config.alert_command_cooldown_s),
+            show_process_net: false,
+            process_net: HashMap::new(),
+            connection_counts: HashMap::new(),
+            show_containers: false,
+            containers: HashMap::new(),
+            show_swap_column: false,
+            process_swap: HashMap::new(),
+            netns_totals: HashMap::new(),
+            netns_sampled: None,
+            tick_rate: Duration::from_millis(config.tick_rate_ms.max(1)),
+            unfocused_tick_rate: unfocused_tick_rate(config),
+            terminal_focused: true,
+            process_interval: Duration::from_millis(config.process_refresh_ms),
+            last_process_refresh: None,
+            command_templates: config.commands.clone(),
+            protected_processes: config.protected.clone(),
+            watchlist: config.watchlist.clone(),
+            keymap,
+            command_target: None,
+            pending_command: None,
+        }
+    }
+
+    fn on_tick(&mut self) {
+        let refresh_start = Instant::now();
+        if self.remote.is_some() {
+            self.refresh_remote();
+        } else {
+            self.refresh_local();
+            self.refresh_threads();
+        }
+        // An exited child lingers as a zombie until reaped; that counts as done
+        if let Some(pid) = self.watch_pid {
+            if self.system.process(pid).is_none_or(|p| p.status() == ProcessStatus::Zombie) {
+                self.watched_exited = true;
+                self.should_quit = true;
+            }
+        }
+
+        let cpu_usage = *self.cpu_history.back().unwrap_or(&0);
+        let mem_percent = *self.mem_history.back().unwrap_or(&0);
+        self.health = HealthLevel::from_usage(cpu_usage, mem_percent);
+        if self.cpu_alert.update(cpu_usage) {
+            self.run_alert_command("cpu", cpu_usage);
+        }
+        if self.mem_alert.update(mem_percent) {
+            self.run_alert_command("memory", mem_percent);
+        }
+
+        if self.auto_follow {
+            self.follow_busiest();
+        }
+
+        self.refresh_duration = refresh_start.elapsed();
+        self.spinner_frame = (self.spinner_frame + 1) % SPINNER_FRAMES.len();
+        self.needs_redraw |= !self.quiet || self.changed_significantly();
+    }
+
+    // Spawned in the background with the alert's details in the environment, so
+    // the dashboard never waits on a slow notifier
+    fn run_alert_command(&mut self, metric: &str, value: u64) {
+        let Some(command) = self.alert_command.clone() else { return };
+        let alert = if metric == "cpu" { &mut self.cpu_alert } else { &mut self.mem_alert };
+        if !alert.command_due(self.alert_command_cooldown) {
+            return;
+        }
+        let threshold = alert.threshold;
+        let spawned = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&command)
+            .env("TERM_DASH_ALERT", metric)
+            .env("TERM_DASH_VALUE", value.to_string())
+            .env("TERM_DASH_THRESHOLD", threshold.to_string())
+            .env("TERM_DASH_HOST", self.host_name())
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        match spawned {
+            Ok(mut child) => {
+                std::thread::spawn(move || child.wait()); // Reaped so it doesn't linger as a zombie
+            }
+            Err(err) => self.set_status(format!("Alert command failed: {}", err)),
+        }
+    }
+
+    // Whether quiet mode should redraw: a gauge or a listed process's CPU moved
+    // noticeably, or processes started or exited. Reshuffles among processes with
+    // near-equal CPU don't count.
+    fn changed_significantly(&self) -> bool {
+        let (cpu, mem, ref process_cpu) = self.drawn;
+        let moved = |history: &VecDeque<u64>, drawn: u64| history.back().is_some_and(|now| now.abs_diff(drawn) > QUIET_THRESHOLD);
+        moved(&self.cpu_history, cpu)
+            || moved(&self.mem_history, mem)
+            || !self.new_pids.is_empty()
+            || !self.vanished_pids.is_empty()
+            || self.processes.iter().any(|info| (info.cpu - process_cpu.get(&info.pid).copied().unwrap_or(0.0)).abs() > QUIET_THRESHOLD as f32)
+    }
+
+    fn mark_drawn(&mut self) {
+        self.needs_redraw = false;
+        self.drawn = (
+            self.cpu_history.back().copied().unwrap_or(0),
+            self.mem_history.back().copied().unwrap_or(0),
+            self.processes.iter().map(|info| (info.pid, info.cpu)).collect(),
+        );
+    }
+
+    fn listed_core_count(&self) -> usize {
+        if self.busiest_cores && !self.core_heatmap { self.core_history.len().min(BUSIEST_CORES) } else { self.core_history.len() }
+    }
+
+    fn toggle_busiest_cores(&mut self) {
+        self.busiest_cores = !self.busiest_cores;
+        self.core_state.select(Some(0));
+    }
+
+    fn toggle_quiet(&mut self) {
+        self.quiet = !self.quiet;
+        self.set_status(if self.quiet { "Quiet mode: redrawing only on input or significant change" } else { "Quiet mode off" });
+    }
+
+    fn toggle_auto_follow(&mut self) {
+        if self.remote.is_some() {
+            self.set_status("Processes are not available from node-exporter");
+            return;
+        }
+        self.auto_follow = !self.auto_follow;
+        if self.auto_follow {
+            self.follow_busiest();
+            self.set_status("Following the busiest process; move the selection to stop");
+        } else {
+            self.set_status("Auto-follow off");
+        }
+    }
+
+    // Row 0 when sorted by CPU, but the list may be sorted otherwise or frozen
+    fn follow_busiest(&mut self) {
+        let busiest = self.processes.iter().enumerate()
+            .filter(|(_, info)| !self.vanished_pids.contains(&info.pid))
+            .min_by(|(_, a), (_, b)| b.cpu.partial_cmp(&a.cpu).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i);
+        if busiest.is_some() {
+            self.process_state.select(busiest);
+        }
+    }
+
+    fn refresh_local(&mut self) {
+        self.system.refresh_all();
+        self.assign_process_aliases();
+        self.networks.refresh(); 
+        self.disks.refresh_list();
+        let disk_info = self.disks.iter().map(|d| DiskInfo {
+            mount: d.mount_point().to_path_buf(),
+            device: block_device_name(Path::new(d.name())),
+            total: d.total_space(),
+            available: d.available_space(),
+            stale: false,
+        }).collect();
+        self.disk_info = self.keep_good_disks(disk_info);
+        self.inode_usage = self.disk_info.iter()
+            .filter_map(|d| inode_usage(&d.mount).map(|p| (d.mount.clone(), p)))
+            .collect();
+        self.record_disk_history();
+        self.record_disk_io();
+
+        // Update History
+        let cpu_usage = self.system.global_cpu_info().cpu_usage() as u64;
+        push_sample(&mut self.cpu_history, cpu_usage, self.raw_history_len);
+        self.record_sample_time();
+        for (history, cpu) in self.core_history.iter_mut().zip(self.system.cpus()) {
+            history.pop_front();
+            history.push_back(cpu.cpu_usage() as u64);
+        }
+
+        let total_mem = self.system.total_memory();
+        let used_mem = self.system.used_memory();
+        let mem_percent = (total_mem > 0).then(|| (used_mem as f64 / total_mem as f64 * 100.0) as u64);
+        self.record_memory(mem_percent);
+        let total_swap = self.system.total_swap();
+        self.swap_percent = if total_swap > 0 { (self.system.used_swap() as f64 / total_swap as f64 * 100.0) as u64 } else { 0 };
+
+        self.zombie_count = self.system.processes().values().filter(|p| p.status() == ProcessStatus::Zombie).count();
+
+        // Walking every process's fds is costly, so only do it when visible
+        if self.show_ports {
+            self.listening_ports = listening_ports();
+        }
+        if self.show_groups {
+            self.group_usage = self.sum_groups();
+        }
+        if self.show_memory_breakdown {
+            self.memory_breakdown = self.break_down_memory();
+        }
 
         // Update Network History
-        let mut total_rx = 0;
-        let mut total_tx = 0;
-        for (_, data) in &self.networks {
-            total_rx += data.received();
-            total_tx += data.transmitted();
-        }
-        self.net_rx_history.pop_front();
-        self.net_rx_history.push_back(total_rx);
-        self.net_tx_history.pop_front();
-        self.net_tx_history.push_back(total_tx);
-
-        // Update Process Cache
+        let mut interfaces = Vec::new();
+        let mut addresses = interface_addresses();
+        for (name, data) in &self.networks {
+            // Interfaces we have no link info for are assumed up rather than dimmed
+            let (up, ips) = addresses.remove(name).unwrap_or((true, Vec::new()));
+            let mac = data.mac_address();
+            interfaces.push(InterfaceInfo {
+                name: name.clone(),
+                rx: data.received(),
+                tx: data.transmitted(),
+                rx_rate: 0.0,
+                tx_rate: 0.0,
+                up,
+                mac: if mac.is_unspecified() { String::new() } else { mac.to_string() },
+                ips,
+            });
+        }
+        self.record_interfaces(interfaces);
+        self.record_context_switches(context_switches());
+        self.record_cpu_breakdown(cpu_times());
+        self.components.refresh();
+        let hottest = self.components.iter().map(|c| c.temperature()).filter(|t| t.is_finite()).reduce(f32::max);
+        self.core_temps = self.components.iter()
+            .filter(|c| c.temperature().is_finite())
+            .filter_map(|c| Some((core_sensor_index(c.label())?, c.temperature())))
+            .collect();
+        self.record_temperature(hottest);
+
+        // Process churn since the last tick; skipped on the first tick, when everything is new
+        let current_pids: HashSet<Pid> = self.system.processes().keys().copied().collect();
+        if !self.known_pids.is_empty() {
+            self.new_pids = current_pids.difference(&self.known_pids).copied().collect();
+            self.vanished_pids = self.known_pids.difference(&current_pids).copied().collect();
+        }
+        self.known_pids = current_pids;
+        self.prune_dead_pids();
+        for (pid, process) in self.system.processes() {
+            let samples = self.cpu_trends.entry(*pid).or_default();
+            if samples.len() == TREND_LEN {
+                samples.pop_front();
+            }
+            samples.push_back(process.cpu_usage());
+        }
+
+        // Walks every process's fds like the ports view, so only while it's the sort key
+        if self.sort_by == SortBy::Connections {
+            self.connection_counts = connection_counts();
+        }
+
+        // Update Process Cache, on its own (possibly slower) schedule
+        let list_due = self.last_process_refresh.is_none_or(|at| at.elapsed() >= self.process_interval);
+        if list_due {
+            self.last_process_refresh = Some(Instant::now());
+        }
+        if self.freeze_list || !list_due {
+            self.refresh_frozen_processes();
+        } else {
+            let departed: Vec<ProcessInfo> = self.processes.iter().filter(|info| self.vanished_pids.contains(&info.pid)).cloned().collect();
+            self.refresh_process_list();
+            self.processes.extend(departed);
+        }
+
+        if self.show_process_net {
+            self.refresh_process_network();
+        }
+        if self.show_containers {
+            self.refresh_containers();
+        }
+        if self.show_swap_column {
+            self.refresh_process_swap();
+        }
+    }
+
+    // node-exporter only exposes running totals, so rates come from the previous scrape
+    fn refresh_remote(&mut self) {
+        let Some(remote) = self.remote.as_mut() else { return };
+        let metrics = match remote.scrape() {
+            Ok(metrics) => metrics,
+            Err(err) => {
+                self.set_status(format!("Scrape failed: {:#}", err));
+                return;
+            }
+        };
+        if let Some(nodename) = &metrics.nodename {
+            remote.host = nodename.clone();
+        }
+        let prev = remote.prev.take().unwrap_or_default();
+
+        // CPU busy % from the change in idle vs total CPU seconds
+        let busy_percent = |idle: f64, total: f64, prev: (f64, f64)| -> u64 {
+            let elapsed = total - prev.1;
+            if elapsed <= 0.0 {
+                return 0;
+            }
+            ((elapsed - (idle - prev.0)) / elapsed * 100.0).clamp(0.0, 100.0) as u64
+        };
+        if self.core_history.len() != metrics.cpus.len() {
+            self.core_history = vec![VecDeque::from(vec![0; HISTORY_LEN]); metrics.cpus.len()];
+        }
+        let (mut idle, mut total, mut prev_idle, mut prev_total) = (0.0, 0.0, 0.0, 0.0);
+        for (history, (cpu, &(cpu_idle, cpu_total))) in self.core_history.iter_mut().zip(&metrics.cpus) {
+            let cpu_prev = prev.cpus.get(cpu).copied().unwrap_or((cpu_idle, cpu_total));
+            history.pop_front();
+            history.push_back(busy_percent(cpu_idle, cpu_total, cpu_prev));
+            idle += cpu_idle;
+            total += cpu_total;
+            prev_idle += cpu_prev.0;
+            prev_total += cpu_prev.1;
+        }
+        push_sample(&mut self.cpu_history, busy_percent(idle, total, (prev_idle, prev_total)), self.raw_history_len);
+        self.record_sample_time();
+
+        let percent_used = |free: u64, total: u64| if total > 0 { (total.saturating_sub(free) as f64 / total as f64 * 100.0) as u64 } else { 0 };
+        self.record_memory((metrics.mem_total > 0).then(|| percent_used(metrics.mem_available, metrics.mem_total)));
+        self.swap_percent = percent_used(metrics.swap_free, metrics.swap_total);
+
+        let disk_info = metrics.filesystems.iter().map(|(mount, fs)| DiskInfo {
+            mount: PathBuf::from(mount),
+            device: String::new(),
+            total: fs.total,
+            available: fs.available,
+            stale: false,
+        }).collect();
+        self.disk_info = self.keep_good_disks(disk_info);
+        self.inode_usage = metrics.filesystems.iter()
+            .filter(|(_, fs)| fs.files > 0)
+            .map(|(mount, fs)| (PathBuf::from(mount), percent_used(fs.files_free, fs.files) as u16))
+            .collect();
+        self.record_disk_history();
+
+        let interfaces = metrics.interfaces.iter().map(|(name, iface)| {
+            let (prev_rx, prev_tx) = prev.interfaces.get(name).map_or((iface.rx, iface.tx), |p| (p.rx, p.tx));
+            InterfaceInfo {
+                name: name.clone(),
+                rx: iface.rx.saturating_sub(prev_rx),
+                tx: iface.tx.saturating_sub(prev_tx),
+                rx_rate: 0.0,
+                tx_rate: 0.0,
+                up: iface.up.unwrap_or(true),
+                mac: iface.mac.clone(),
+                ips: Vec::new(),
+            }
+        }).collect();
+        self.record_interfaces(interfaces);
+        self.record_context_switches(metrics.context_switches);
+        self.record_cpu_breakdown(Some(metrics.cpu_times));
+        self.record_temperature(metrics.max_temperature);
+
+        if let Some(remote) = self.remote.as_mut() {
+            remote.prev = Some(metrics);
+        }
+    }
+
+    // A disk caught mid-unmount or on a flaky mount can briefly report a size of 0;
+    // keep its last good reading (marked stale) rather than flashing an empty disk
+    fn keep_good_disks(&self, mut fresh: Vec<DiskInfo>) -> Vec<DiskInfo> {
+        for disk in fresh.iter_mut().filter(|disk| disk.total == 0) {
+            if let Some(last) = self.disk_info.iter().find(|last| last.mount == disk.mount && last.total > 0) {
+                *disk = DiskInfo { stale: true, ..last.clone() };
+            }
+        }
+        fresh
+    }
+
+    // None when the source reported no total memory; the last sample is repeated
+    // so the graph keeps moving, and the gauge says it's stale
+    fn record_memory(&mut self, percent: Option<u64>) {
+        self.mem_stale = percent.is_none() && !self.mem_history.is_empty();
+        let percent = percent.unwrap_or_else(|| *self.mem_history.back().unwrap_or(&0));
+        push_sample(&mut self.mem_history, percent, self.raw_history_len);
+    }
+
+    // Per-mount usage history; unmounted disks drop out
+    fn record_disk_history(&mut self) {
+        let mut disk_history = HashMap::new();
+        for disk in &self.disk_info {
+            let mut history = self.disk_history.remove(&disk.mount).unwrap_or_else(|| VecDeque::from(vec![0; HISTORY_LEN]));
+            history.pop_front();
+            history.push_back(disk_used_percent(disk));
+            disk_history.insert(disk.mount.clone(), history);
+        }
+        self.disk_history = disk_history;
+    }
+
+    // Rates from the change in /proc/diskstats sector counts over the measured interval
+    fn record_disk_io(&mut self) {
+        let Some(sectors) = disk_sectors() else { return };
+        let now = Instant::now();
+        let seconds = self.disk_io_sampled.map_or(self.tick_rate, |at| now - at).as_secs_f64().max(0.001);
+        self.disk_io_sampled = Some(now);
+        let rate = |now: u64, before: u64| (now.saturating_sub(before) as f64 * SECTOR_SIZE as f64 / seconds) as u64;
+        self.disk_io = sectors.iter()
+            .filter_map(|(device, &(read, written))| {
+                let &(prev_read, prev_written) = self.disk_sectors.get(device)?;
+                Some((device.clone(), (rate(read, prev_read), rate(written, prev_written))))
+            })
+            .collect();
+        self.disk_sectors = sectors;
+    }
+
+    // Interface counters are bytes since the previous refresh. Rates divide by the
+    // measured interval rather than the tick rate, since ticks stretch while unfocused
+    // or when a scrape is slow.
+    fn record_interfaces(&mut self, mut interfaces: Vec<InterfaceInfo>) {
+        let total_rx: u64 = interfaces.iter().map(|iface| iface.rx).sum();
+        let total_tx: u64 = interfaces.iter().map(|iface| iface.tx).sum();
+        let now = Instant::now();
+        let seconds = self.net_sampled.map_or(self.tick_rate, |at| now - at).as_secs_f64().max(0.001);
+        self.net_sampled = Some(now);
+        for iface in &mut interfaces {
+            iface.rx_rate = iface.rx as f64 / seconds;
+            iface.tx_rate = iface.tx as f64 / seconds;
+        }
+        interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+        self.interfaces = interfaces;
+
+        push_sample(&mut self.net_rx_history, (total_rx as f64 / seconds) as u64, self.raw_history_len);
+        push_sample(&mut self.net_tx_history, (total_tx as f64 / seconds) as u64, self.raw_history_len);
+        let rx_sum = self.net_rx_total_history.back().copied().unwrap_or(0) + total_rx;
+        let tx_sum = self.net_tx_total_history.back().copied().unwrap_or(0) + total_tx;
+        push_sample(&mut self.net_rx_total_history, rx_sum, self.raw_history_len);
+        push_sample(&mut self.net_tx_total_history, tx_sum, self.raw_history_len);
+    }
+
+    fn record_cpu_breakdown(&mut self, times: Option<CpuTimes>) {
+        if let Some(times) = times {
+            let breakdown = self.prev_cpu_times.map_or_else(CpuBreakdown::default, |prev| times.breakdown_since(&prev));
+            self.prev_cpu_times = Some(times);
+            self.cpu_breakdown_history.pop_front();
+            self.cpu_breakdown_history.push_back(breakdown);
+        }
+    }
+
+    fn record_temperature(&mut self, celsius: Option<f32>) {
+        self.temperature = celsius;
+        if let Some(celsius) = celsius {
+            push_sample(&mut self.temp_history, celsius.max(0.0) as u64, self.raw_history_len);
+        }
+    }
+
+    // Per second over the time actually elapsed, so a late tick (or one after a
+    // suspend) doesn't show as a spike
+    fn record_context_switches(&mut self, total: Option<u64>) {
+        if let Some(ctxt) = total {
+            let now = Instant::now();
+            let seconds = self.ctxt_sampled.map_or(self.tick_rate, |at| now - at).as_secs_f64().max(0.001);
+            self.ctxt_sampled = Some(now);
+            let delta = self.prev_ctxt.map_or(0, |prev| ctxt.saturating_sub(prev));
+            self.prev_ctxt = Some(ctxt);
+            push_sample(&mut self.ctxt_history, (delta as f64 / seconds) as u64, self.raw_history_len);
+        }
+    }
+
+    fn record_sample_time(&mut self) {
+        if self.sample_times.len() >= self.raw_history_len {
+            self.sample_times.pop_front();
+        }
+        self.sample_times.push_back(SystemTime::now());
+    }
+
+    fn toggle_real_time_axis(&mut self) {
+        self.real_time_axis = !self.real_time_axis;
+        self.set_status(if self.real_time_axis { "CPU and memory graphs on a wall-clock axis" } else { "CPU and memory graphs one column per tick" });
+    }
+
+    // A process stays in its container, so each PID is only looked up once
+    fn refresh_containers(&mut self) {
+        for info in &self.processes {
+            self.containers.entry(info.pid).or_insert_with(|| process_container(info.pid));
+        }
+    }
+
+    fn toggle_containers(&mut self) {
+        if !cfg!(target_os = "linux") {
+            self.set_status("Containers are only detected on Linux");
+            return;
+        }
+        self.show_containers = !self.show_containers;
+        if self.show_containers {
+            self.refresh_containers();
+        }
+    }
+
+    fn refresh_process_swap(&mut self) {
+        self.process_swap = self.processes.iter().filter_map(|info| Some((info.pid, process_swap(info.pid)?))).collect();
+    }
+
+    fn toggle_swap_column(&mut self) {
+        if !cfg!(target_os = "linux") {
+            self.set_status("Per-process swap is only available on Linux");
+            return;
+        }
+        self.show_swap_column = !self.show_swap_column;
+        if self.show_swap_column {
+            self.refresh_process_swap();
+        }
+    }
+
+    // Traffic can only be attributed per network namespace, so processes sharing
+    // the host namespace get no figure; containerised ones get their namespace's
+    fn refresh_process_network(&mut self) {
+        let host_ns = net_namespace("self");
+        let now = Instant::now();
+        let seconds = self.netns_sampled.map_or(self.tick_rate, |at| now - at).as_secs_f64().max(0.001);
+        self.netns_sampled = Some(now);
+        let rate = |now: u64, before: u64| (now.saturating_sub(before) as f64 / seconds) as u64;
+        let mut totals = HashMap::new();
+        self.process_net.clear();
+        for info in &self.processes {
+            let pid = info.pid.to_string();
+            let Some(ns) = net_namespace(&pid) else { continue };
+            if Some(ns) == host_ns {
+                continue;
+            }
+            let Some((rx, tx)) = namespace_net_totals(&pid) else { continue };
+            totals.insert(ns, (rx, tx));
+            if let Some(&(prev_rx, prev_tx)) = self.netns_totals.get(&ns) {
+                self.process_net.insert(info.pid, (rate(rx, prev_rx), rate(tx, prev_tx)));
+            }
+        }
+        self.netns_totals = totals;
+    }
+
+    fn refresh_process_list(&mut self) {
         let mut procs: Vec<_> = self.system.processes().values().collect();
+        // Reading every process's CPU time is only worth it while the column shows it
+        let cpu_times: HashMap<Pid, Duration> = if self.show_cpu_time {
+            procs.iter().filter_map(|p| Some((p.pid(), process_cpu_time(p.pid())?))).collect()
+        } else {
+            HashMap::new()
+        };
+
+        match self.sort_by {
+            SortBy::Cpu if self.show_cpu_time => procs.sort_by_key(|p| std::cmp::Reverse(cpu_times.get(&p.pid()).copied().unwrap_or_default())),
+            SortBy::Cpu => procs.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap_or(std::cmp::Ordering::Equal)),
+            SortBy::Memory => procs.sort_by_key(|p| std::cmp::Reverse(p.memory())),
+            SortBy::Trend => procs.sort_by(|a, b| self.cpu_trend(b.pid()).partial_cmp(&self.cpu_trend(a.pid())).unwrap_or(std::cmp::Ordering::Equal)),
+            // Unreadable processes have no count and sort below those with none
+            SortBy::Connections => procs.sort_by_key(|p| std::cmp::Reverse(self.connection_counts.get(&p.pid()).copied())),
+        }
+        
+        if let Some(pid) = self.watch_pid {
+            procs.retain(|p| p.pid() == pid);
+        }
+        if let Some(filter) = &self.search_filter {
+            procs.retain(|p| filter.matches(p));
+        } else if let Some(limit) = PROCESS_LIMITS[self.process_limit] {
+            // Watched processes stay listed below the cutoff, in sort order
+            let mut rank = 0;
+            procs.retain(|p| {
+                rank += 1;
+                rank <= limit || self.watched(p.name())
+            });
+        }
         
-        if !self.search_query.is_empty() {
-            procs.retain(|p| p.name().to_lowercase().contains(&self.search_query.to_lowercase()));
-            procs.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap_or(std::cmp::Ordering::Equal));
+        let total_memory = self.system.total_memory();
+        self.processes = procs.iter().map(|p| ProcessInfo {
+            pid: p.pid(),
+            name: p.name().to_string(),
+            cpu: p.cpu_usage(),
+            cpu_trend: self.cpu_trend(p.pid()),
+            cpu_time: cpu_times.get(&p.pid()).copied(),
+            mem: p.memory(),
+            mem_percent: memory_percent(p.memory(), total_memory),
+            start_time: p.start_time(),
+            status: p.status(),
+            exe: p.exe().map(|path| path.display().to_string()).unwrap_or_default(),
+            cmd: p.cmd().join(" "),
+        }).collect();
+        self.unlisted = self.sum_unlisted();
+    }
+
+    fn watched(&self, name: &str) -> bool {
+        self.show_watchlist && self.watchlist.iter().any(|watched| watched == name)
+    }
+
+    fn toggle_watchlist(&mut self) {
+        if self.watchlist.is_empty() {
+            self.set_status("No watchlist in config.toml");
+            return;
+        }
+        self.show_watchlist = !self.show_watchlist;
+        self.set_status(if self.show_watchlist { "Watchlist on" } else { "Watchlist off" });
+    }
+
+    // Everything below the top-N cutoff, summed so the list's totals still add up.
+    // None when nothing is cut off, or when a filter or --watch hides the rest.
+    fn sum_unlisted(&self) -> Option<(usize, f32, u64)> {
+        if self.search_filter.is_some() || self.watch_pid.is_some() {
+            return None;
+        }
+        PROCESS_LIMITS[self.process_limit]?;
+        let listed: HashSet<Pid> = self.processes.iter().map(|info| info.pid).collect();
+        let (count, cpu, mem) = self.system.processes().values()
+            .filter(|p| !listed.contains(&p.pid()))
+            .fold((0, 0.0, 0), |(count, cpu, mem), p| (count + 1, cpu + p.cpu_usage(), mem + p.memory()));
+        (count > 0).then_some((count, cpu, mem))
+    }
+
+    // Re-parses the query and re-filters the processes sampled on the last tick, so
+    // results follow each keystroke instead of the next tick. `file:` and `port:`
+    // walk every process's fds, so they're only resolved once `submitted`; until then
+    // the list stays unfiltered. A frozen list keeps its rows.
+    fn apply_search(&mut self, submitted: bool) {
+        let walks_fds = self.search_query.starts_with("file:") || self.search_query.starts_with("port:");
+        self.search_filter = if self.search_query.is_empty() || (walks_fds && !submitted) {
+            None
+        } else {
+            ProcessFilter::parse(&self.search_query, self.search_case_sensitive)
+        };
+        if self.remote.is_some() || self.freeze_list {
+            return;
+        }
+        self.refresh_process_list();
+        self.process_state.select(if self.processes.is_empty() { None } else { Some(0) });
+    }
+
+    // Keeps the list's order (and so the selection) but refreshes its metrics
+    fn refresh_frozen_processes(&mut self) {
+        // Exited processes stay for the tick they vanish on, then drop out
+        self.processes.retain(|info| self.system.process(info.pid).is_some() || self.vanished_pids.contains(&info.pid));
+        let total_memory = self.system.total_memory();
+        for info in &mut self.processes {
+            if let Some(process) = self.system.process(info.pid) {
+                info.cpu = process.cpu_usage();
+                info.cpu_trend = self.cpu_trends.get(&info.pid).map_or(0.0, slope);
+                if self.show_cpu_time {
+                    info.cpu_time = process_cpu_time(info.pid);
+                }
+                info.mem = process.memory();
+                info.mem_percent = memory_percent(process.memory(), total_memory);
+                info.status = process.status();
+            }
+        }
+        self.unlisted = self.sum_unlisted();
+    }
+
+    fn toggle_ports(&mut self) {
+        if self.remote.is_some() {
+            self.set_status("Listening ports are not available from node-exporter");
+            return;
+        }
+        self.show_ports = !self.show_ports;
+        if self.show_ports {
+            self.show_groups = false;
+            self.show_memory_breakdown = false;
+            self.listening_ports = listening_ports();
+        }
+    }
+
+    fn toggle_groups(&mut self) {
+        if self.remote.is_some() {
+            self.set_status("Process groups are not available from node-exporter");
+            return;
+        }
+        self.show_groups = !self.show_groups;
+        if self.show_groups {
+            self.show_ports = false;
+            self.show_memory_breakdown = false;
+            self.group_usage = self.sum_groups();
+        }
+    }
+
+    // One entry per configured group plus "other" for everything unmatched.
+    // Threads share their process's memory, so only processes are counted.
+    fn sum_groups(&self) -> Vec<GroupUsage> {
+        let mut usage: Vec<GroupUsage> = self.process_groups.iter()
+            .map(|group| group.name.clone())
+            .chain(std::iter::once("other".to_string()))
+            .map(|name| GroupUsage { name, processes: 0, cpu: 0.0, mem: 0 })
+            .collect();
+        for process in self.system.processes().values().filter(|p| p.thread_kind() != Some(ThreadKind::Userland)) {
+            let index = self.process_groups.iter()
+                .position(|group| group.pattern.is_match(process.name()))
+                .unwrap_or(self.process_groups.len());
+            let entry = &mut usage[index];
+            entry.processes += 1;
+            entry.cpu += process.cpu_usage();
+            entry.mem += process.memory();
+        }
+        usage
+    }
+
+    fn toggle_memory_breakdown(&mut self) {
+        if self.remote.is_some() {
+            self.set_status("Processes are not available from node-exporter");
+            return;
+        }
+        self.show_memory_breakdown = !self.show_memory_breakdown;
+        if self.show_memory_breakdown {
+            self.show_ports = false;
+            self.show_groups = false;
+            self.memory_breakdown = self.break_down_memory();
+        }
+    }
+
+    // The biggest processes by resident memory, everything else summed, then
+    // reclaimable cache and free memory. Shared pages count towards each process
+    // mapping them, so the process bars can add up to more than is in use.
+    fn break_down_memory(&self) -> Vec<(String, u64)> {
+        let mut processes: Vec<&Process> = self.system.processes().values().filter(|p| p.thread_kind() != Some(ThreadKind::Userland)).collect();
+        processes.sort_by_key(|p| std::cmp::Reverse(p.memory()));
+        let mut breakdown: Vec<(String, u64)> = processes.iter()
+            .take(MEMORY_TOP_PROCESSES)
+            .map(|p| (self.process_label(p.pid(), p.name()), p.memory()))
+            .collect();
+        let others = processes.iter().skip(MEMORY_TOP_PROCESSES).map(|p| p.memory()).sum();
+        let free = self.system.free_memory();
+        breakdown.push(("others".to_string(), others));
+        breakdown.push(("cache".to_string(), self.system.available_memory().saturating_sub(free)));
+        breakdown.push(("free".to_string(), free));
+        breakdown
+    }
+
+    // Per-PID maps would otherwise keep an entry for every process that ever ran
+    fn prune_dead_pids(&mut self) {
+        let alive = &self.known_pids;
+        self.process_aliases.retain(|pid, _| alive.contains(pid));
+        self.process_net.retain(|pid, _| alive.contains(pid));
+        self.containers.retain(|pid, _| alive.contains(pid));
+        self.cpu_trends.retain(|pid, _| alive.contains(pid));
+        // Killed rows stay struck through for the tick they are shown as vanished
+        self.killed_pids.retain(|pid| alive.contains(pid) || self.vanished_pids.contains(pid));
+    }
+
+    fn cpu_trend(&self, pid: Pid) -> f32 {
+        self.cpu_trends.get(&pid).map_or(0.0, slope)
+    }
+
+    // Numbers new PIDs in order, so a process keeps its label for as long as it lives
+    fn assign_process_aliases(&mut self) {
+        if !self.anonymize {
+            return;
+        }
+        let mut pids: Vec<Pid> = self.system.processes().keys().filter(|pid| !self.process_aliases.contains_key(pid)).copied().collect();
+        pids.sort();
+        for pid in pids {
+            self.process_aliases.insert(pid, self.next_alias);
+            self.next_alias += 1;
+        }
+    }
+
+    // Nobody is watching an unfocused terminal, so refresh slower or not at all
+    fn current_tick_rate(&self) -> Option<Duration> {
+        if self.terminal_focused { Some(self.tick_rate) } else { self.unfocused_tick_rate }
+    }
+
+    // Disks in the Disks panel, narrowed to the near-full ones when toggled
+    fn visible_disks(&self) -> Vec<&DiskInfo> {
+        self.disk_info.iter().filter(|disk| !self.only_full_disks || disk_used_percent(disk) >= self.disk_full_threshold).collect()
+    }
+
+    fn toggle_full_disks(&mut self) {
+        self.only_full_disks = !self.only_full_disks;
+        self.disk_state.select(Some(0));
+    }
+
+    fn host_name(&self) -> String {
+        match &self.remote {
+            Some(remote) => remote.host.clone(),
+            None => System::host_name().unwrap_or_else(|| "Unknown".to_string()),
+        }
+    }
+
+    // Raw counts such as bytes per tick, grouped as 1,048,576 when toggled on
+    fn format_count(&self, n: u64) -> String {
+        if !self.group_digits {
+            return n.to_string();
+        }
+        let digits = n.to_string();
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    // The Name column's text for a process, given the column's width in cells
+    fn display_name(&self, info: &ProcessInfo, width: usize) -> String {
+        // Kernel threads have no exe or command line; fall back to the name
+        let full_name = Path::new(&info.exe).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| info.name.clone());
+        match self.name_display {
+            _ if self.anonymize => self.process_label(info.pid, &info.name),
+            NameDisplay::Path if !info.exe.is_empty() => info.exe.clone(),
+            NameDisplay::Command if !info.cmd.is_empty() => info.cmd.clone(),
+            _ => match self.name_length {
+                NameLength::Auto if full_name.chars().count() <= width => full_name,
+                NameLength::Long => full_name,
+                _ => info.name.clone(),
+            },
+        }
+    }
+
+    fn process_label(&self, pid: Pid, name: &str) -> String {
+        if !self.anonymize {
+            return name.to_string();
+        }
+        match self.process_aliases.get(&pid) {
+            Some(alias) => format!("process-{}", alias),
+            None => "process".to_string(),
+        }
+    }
+
+    fn export_markdown(&mut self) {
+        match save_markdown(self) {
+            Ok(path) => self.set_status(format!("Process list saved to {}", path.display())),
+            Err(err) => self.set_status(format!("Markdown export failed: {:#}", err)),
+        }
+    }
+
+    // The active preset with any `[theme]` colours from config.toml laid over it
+    fn theme(&self) -> Theme {
+        self.theme_overrides.apply(self.current_theme.get_theme())
+    }
+
+    fn export_theme(&mut self) {
+        match save_theme(self) {
+            Ok(path) => self.set_status(format!("Theme saved to {}", path.display())),
+            Err(err) => self.set_status(format!("Theme export failed: {:#}", err)),
+        }
+    }
+
+    // Creates the directory if needed so new users have somewhere to put config.toml
+    fn open_config_dir(&mut self) {
+        let Some(dir) = Config::path().and_then(|path| Some(path.parent()?.to_path_buf())) else {
+            self.set_status("No config directory on this platform");
+            return;
+        };
+        if let Err(err) = std::fs::create_dir_all(&dir) {
+            self.set_status(format!("Failed to create {}: {}", dir.display(), err));
+        } else if open_in_file_manager(&dir) {
+            self.set_status(format!("Opened {}", dir.display()));
+        } else {
+            self.set_status(format!("Config directory: {}", dir.display()));
+        }
+    }
+
+    fn export_process_tree(&mut self) {
+        if self.remote.is_some() {
+            self.set_status("Processes are not available from node-exporter");
+            return;
+        }
+        match save_process_tree(self) {
+            Ok(path) => self.set_status(format!("Process tree saved to {}", path.display())),
+            Err(err) => self.set_status(format!("Process tree export failed: {:#}", err)),
+        }
+    }
+
+    // Lists the selected process's threads under it, to find the hot one
+    fn toggle_threads(&mut self) {
+        if !cfg!(target_os = "linux") {
+            self.set_status("Threads are only available on Linux");
+            return;
+        }
+        let selected = self.process_state.selected().and_then(|i| self.processes.get(i)).map(|info| info.pid);
+        if selected.is_none() || self.expanded_pid == selected {
+            self.expanded_pid = None;
+            self.threads.clear();
+            return;
+        }
+        self.expanded_pid = selected;
+        self.threads.clear();
+        self.thread_sampled = None;
+        self.refresh_threads();
+    }
+
+    // Per-thread CPU% is the CPU time each thread used since the previous sample
+    fn refresh_threads(&mut self) {
+        let Some(pid) = self.expanded_pid else { return };
+        let samples = thread_cpu_times(pid);
+        if samples.is_empty() {
+            // The process exited
+            self.expanded_pid = None;
+            self.threads.clear();
+            return;
+        }
+        let elapsed = self.thread_sampled.map(|at| at.elapsed().as_secs_f32());
+        self.thread_sampled = Some(Instant::now());
+        let previous: HashMap<u32, Duration> = self.threads.iter().map(|t| (t.tid, t.cpu_time)).collect();
+        self.threads = samples.into_iter().map(|(tid, name, cpu_time)| {
+            let cpu = match (elapsed, previous.get(&tid)) {
+                (Some(secs), Some(prev)) if secs > 0.0 => cpu_time.saturating_sub(*prev).as_secs_f32() / secs * 100.0,
+                _ => 0.0,
+            };
+            ThreadInfo { tid, name, cpu, cpu_time }
+        }).collect();
+        if self.show_cpu_time {
+            self.threads.sort_by_key(|t| std::cmp::Reverse(t.cpu_time));
+        } else {
+            self.threads.sort_by(|a, b| b.cpu.partial_cmp(&a.cpu).unwrap_or(std::cmp::Ordering::Equal));
+        }
+    }
+
+    // A process idle now may still have burned hours of CPU; this shows which
+    fn toggle_cpu_time(&mut self) {
+        if !cfg!(target_os = "linux") {
+            self.set_status("CPU time is only available on Linux");
+            return;
+        }
+        self.show_cpu_time = !self.show_cpu_time;
+        if self.freeze_list {
+            self.refresh_frozen_processes();
+        } else {
+            self.refresh_process_list();
+        }
+    }
+
+    fn toggle_trend_sort(&mut self) {
+        self.sort_by = if self.sort_by == SortBy::Trend { SortBy::Cpu } else { SortBy::Trend };
+        if !self.freeze_list {
+            self.refresh_process_list();
+        }
+    }
+
+    fn toggle_connection_sort(&mut self) {
+        if !cfg!(target_os = "linux") {
+            self.set_status("Connection counts are only available on Linux");
+            return;
+        }
+        self.sort_by = if self.sort_by == SortBy::Connections { SortBy::Cpu } else { SortBy::Connections };
+        if self.sort_by == SortBy::Connections {
+            self.connection_counts = connection_counts();
+        }
+        if !self.freeze_list {
+            self.refresh_process_list();
+        }
+    }
+
+    // Quick switch between the default CPU ordering and memory hogs
+    fn toggle_memory_sort(&mut self) {
+        self.sort_by = if self.sort_by == SortBy::Memory { SortBy::Cpu } else { SortBy::Memory };
+        if !self.freeze_list {
+            self.refresh_process_list();
+        }
+    }
+
+    fn change_process_limit(&mut self, grow: bool) {
+        self.process_limit = if grow {
+            (self.process_limit + 1).min(PROCESS_LIMITS.len() - 1)
+        } else {
+            self.process_limit.saturating_sub(1)
+        };
+        if !self.freeze_list {
+            self.refresh_process_list();
+        }
+    }
+
+    fn acknowledge_alerts(&mut self) {
+        for alert in [&mut self.cpu_alert, &mut self.mem_alert] {
+            if alert.firing {
+                alert.acknowledged = true;
+            }
+        }
+    }
+
+    fn set_status(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), Instant::now()));
+    }
+
+    fn reset_history(&mut self) {
+        let histories = [&mut self.cpu_history, &mut self.mem_history, &mut self.net_rx_history, &mut self.net_tx_history, &mut self.net_rx_total_history, &mut self.net_tx_total_history, &mut self.ctxt_history, &mut self.temp_history];
+        for history in histories.into_iter().chain(self.core_history.iter_mut()) {
+            history.clear();
+            history.resize(HISTORY_LEN, 0);
+        }
+        self.disk_history.clear();
+        self.sample_times.clear();
+        self.cpu_breakdown_history.clear();
+        self.cpu_breakdown_history.resize(HISTORY_LEN, CpuBreakdown::default());
+    }
+
+    fn next_process(&mut self) {
+        self.auto_follow = false;
+        select_next(&mut self.process_state, self.processes.len());
+    }
+
+    fn previous_process(&mut self) {
+        self.auto_follow = false;
+        select_previous(&mut self.process_state, self.processes.len());
+    }
+
+    // Up/Down act on whichever panel currently has focus
+    fn next_item(&mut self) {
+        match self.focused_panel {
+            FocusedPanel::Processes => self.next_process(),
+            FocusedPanel::Disks => {
+                let count = self.visible_disks().len();
+                select_next(&mut self.disk_state, count);
+            }
+            FocusedPanel::Network => select_next(&mut self.network_state, self.interfaces.len()),
+            FocusedPanel::Cores => {
+                let count = self.listed_core_count();
+                select_next(&mut self.core_state, count);
+            }
+        }
+    }
+
+    fn previous_item(&mut self) {
+        match self.focused_panel {
+            FocusedPanel::Processes => self.previous_process(),
+            FocusedPanel::Disks => {
+                let count = self.visible_disks().len();
+                select_previous(&mut self.disk_state, count);
+            }
+            FocusedPanel::Network => select_previous(&mut self.network_state, self.interfaces.len()),
+            FocusedPanel::Cores => {
+                let count = self.listed_core_count();
+                select_previous(&mut self.core_state, count);
+            }
+        }
+    }
+
+    fn kill_selected_process(&mut self) {
+        if let Some(i) = self.process_state.selected() {
+            if let Some(info) = self.processes.get(i) {
+                if self.protected_processes.contains(&info.name) {
+                    let message = format!("Refusing to kill {}: it is on the protected list", info.name);
+                    self.set_status(message);
+                } else if let Some(process) = self.system.process(info.pid) {
+                    // SIGTERM lets the process clean up; platforms without it get a plain kill
+                    let pid = info.pid;
+                    let (sent, signal) = match process.kill_with(Signal::Term) {
+                        Some(sent) => (sent, "SIGTERM"),
+                        None => (process.kill(), "kill signal"),
+                    };
+                    if sent {
+                        self.killed_pids.insert(pid);
+                        self.set_status(format!("Sent {} to {}", signal, pid));
+                    } else {
+                        self.set_status(format!("Failed to signal {}", pid));
+                    }
+                }
+            }
+        }
+    }
+
+    fn edit_affinity(&mut self) {
+        if cfg!(not(target_os = "linux")) {
+            self.set_status("CPU affinity is only supported on Linux");
+            return;
+        }
+        if let Some(pid) = self.selected_pid {
+            self.affinity_input = get_affinity(pid).map(|cores| format_core_list(&cores)).unwrap_or_default();
+            self.input_mode = InputMode::Affinity;
+        }
+    }
+
+    fn copy_command(&mut self) {
+        let Some(process) = self.selected_pid.and_then(|pid| self.system.process(pid)) else { return };
+        if self.anonymize {
+            self.set_status("Command lines are hidden while anonymised");
+            return;
+        }
+        let cmd = process.cmd().join(" ");
+        if cmd.is_empty() {
+            self.set_status("Process has no command line");
+            return;
+        }
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(err) => {
+                    self.set_status(format!("Clipboard unavailable: {}", err));
+                    return;
+                }
+            }
+        }
+        let copied = self.clipboard.as_mut().map(|clipboard| clipboard.set_text(cmd.as_str()));
+        match copied {
+            Some(Ok(())) => self.set_status(format!("Copied command ({} chars)", cmd.chars().count())),
+            Some(Err(err)) => self.set_status(format!("Copy failed: {}", err)),
+            None => {}
+        }
+    }
+
+    fn apply_affinity(&mut self) {
+        self.input_mode = InputMode::Details;
+        let Some(pid) = self.selected_pid else { return };
+        let Some(cores) = parse_core_list(&self.affinity_input, self.system.cpus().len()) else {
+            self.set_status(format!("Invalid core list '{}'", self.affinity_input));
+            return;
+        };
+        match set_affinity(pid, &cores) {
+            Ok(()) => self.set_status(format!("Pinned {} to cores {}", pid, format_core_list(&cores))),
+            Err(e) => self.set_status(format!("Failed to set affinity for {}: {}", pid, e)),
+        }
+    }
+
+    fn jump_to_pid(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let Ok(pid) = self.pid_input.parse::<u32>() else { return };
+        let pid = Pid::from_u32(pid);
+        if let Some(i) = self.processes.iter().position(|info| info.pid == pid) {
+            self.focused_panel = FocusedPanel::Processes;
+            self.process_state.select(Some(i));
+        } else if self.system.process(pid).is_some() {
+            self.set_status(format!("PID {} is not in the current list", pid));
+        } else {
+            self.set_status(format!("No process with PID {}", pid));
+        }
+    }
+
+    fn open_command_menu(&mut self) {
+        let selected = self.process_state.selected().and_then(|i| self.processes.get(i));
+        if let Some(info) = selected {
+            self.command_target = Some(info.pid);
+            self.input_mode = InputMode::Commands;
+        }
+    }
+
+    fn choose_command(&mut self, index: usize) {
+        let (Some(template), Some(pid)) = (self.command_templates.get(index), self.command_target) else { return };
+        self.pending_command = Some((template.command.replace("{pid}", &pid.to_string()), pid));
+        self.input_mode = InputMode::Normal;
+    }
+
+    // Bucketed graphs need a minute of samples, and how many that is depends on the rate
+    fn set_tick_rate(&mut self, tick_rate: Duration) {
+        self.tick_rate = tick_rate;
+        self.raw_history_len = HISTORY_LEN * Granularity::Minute.samples_per_bucket(self.tick_rate);
+        let histories = [&mut self.cpu_history, &mut self.mem_history, &mut self.net_rx_history, &mut self.net_tx_history, &mut self.net_rx_total_history, &mut self.net_tx_total_history, &mut self.ctxt_history, &mut self.temp_history];
+        for history in histories {
+            history.drain(..history.len().saturating_sub(self.raw_history_len));
+        }
+        self.sample_times.drain(..self.sample_times.len().saturating_sub(self.raw_history_len));
+    }
+
+    fn top_view(&self) -> TopView {
+        if self.show_groups {
+            TopView::Groups
+        } else if self.show_ports {
+            TopView::Ports
+        } else if self.show_memory_breakdown {
+            TopView::Memory
+        } else {
+            TopView::Graphs
+        }
+    }
+
+    fn set_top_view(&mut self, view: TopView) {
+        self.show_groups = false;
+        self.show_ports = false;
+        self.show_memory_breakdown = false;
+        match view {
+            TopView::Graphs => {}
+            TopView::Groups => self.toggle_groups(),
+            TopView::Ports => self.toggle_ports(),
+            TopView::Memory => self.toggle_memory_breakdown(),
+        }
+    }
+
+    fn cycle_profile(&mut self) {
+        if self.profiles.is_empty() {
+            self.set_status("No [[profiles]] in config.toml; press + to save the current layout as one");
+            return;
+        }
+        let index = self.profile_index.map_or(0, |i| (i + 1) % self.profiles.len());
+        let profile = self.profiles[index].clone();
+        if let Some(view) = profile.view {
+            self.set_top_view(view);
+        }
+        self.show_gauges = profile.gauges.unwrap_or(self.show_gauges);
+        self.portrait = profile.portrait.unwrap_or(self.portrait);
+        self.process_fullscreen = profile.fullscreen.unwrap_or(self.process_fullscreen);
+        self.details_sidebar = profile.sidebar.unwrap_or(self.details_sidebar);
+        self.core_heatmap = profile.heatmap.unwrap_or(self.core_heatmap);
+        self.gridded_graphs = profile.gridlines.unwrap_or(self.gridded_graphs);
+        self.density = profile.density.unwrap_or(self.density);
+        self.profile_index = Some(index);
+        self.set_status(format!("Layout profile '{}' ({}/{})", profile.name, index + 1, self.profiles.len()));
+    }
+
+    // Appends the current layout to config.toml, leaving the rest of the file as written
+    fn save_profile(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let name = self.profile_name_input.trim().to_string();
+        if name.is_empty() {
+            self.set_status("Profile name can't be empty");
+            return;
+        }
+        if self.profiles.iter().any(|profile| profile.name == name) {
+            self.set_status(format!("A profile named '{}' is already in config.toml", name));
+            return;
+        }
+        let profile = LayoutProfile {
+            name: name.clone(),
+            view: Some(self.top_view()),
+            gauges: Some(self.show_gauges),
+            portrait: Some(self.portrait),
+            fullscreen: Some(self.process_fullscreen),
+            sidebar: Some(self.details_sidebar),
+            heatmap: Some(self.core_heatmap),
+            gridlines: Some(self.gridded_graphs),
+            density: Some(self.density),
+        };
+        match append_to_config(&profile.to_toml()) {
+            Ok(path) => {
+                self.profile_index = Some(self.profiles.len());
+                self.profiles.push(profile);
+                self.set_status(format!("Saved profile '{}' to {}", name, path.display()));
+            }
+            Err(err) => self.set_status(format!("Profile not saved: {:#}", err)),
+        }
+    }
+
+    fn apply_tick_rate_input(&mut self) {
+        self.input_mode = InputMode::Normal;
+        match self.tick_rate_input.parse::<u64>() {
+            Ok(ms) if ms > 0 => {
+                self.set_tick_rate(Duration::from_millis(ms));
+                self.set_status(format!("Refresh interval set to {} ms", ms));
+            }
+            _ => self.set_status("Refresh interval must be a positive number of milliseconds"),
+        }
+    }
+
+    // Re-reads config.toml and applies what can change live; a broken file leaves
+    // the current settings in place
+    fn reload_config(&mut self) {
+        let loaded = Config::load().and_then(|config| Ok((config.keymap()?, config)));
+        let (keymap, config) = match loaded {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                self.set_status(format!("Config not reloaded: {:#}", err));
+                return;
+            }
+        };
+        self.keymap = keymap;
+        self.set_tick_rate(Duration::from_millis(config.tick_rate_ms.max(1)));
+        self.unfocused_tick_rate = unfocused_tick_rate(&config);
+        self.process_interval = Duration::from_millis(config.process_refresh_ms);
+        self.disk_full_threshold = config.disk_full_threshold;
+        self.disk_units = config.disk_units;
+        self.cpu_alert.threshold = config.cpu_alert;
+        self.mem_alert.threshold = config.mem_alert;
+        self.alert_command = config.alert_command;
+        self.alert_command_cooldown = Duration::from_secs(config.alert_command_cooldown_s);
+        self.command_templates = config.commands;
+        self.protected_processes = config.protected;
+        self.watchlist = config.watchlist;
+        self.process_groups = config.groups;
+        self.profiles = config.profiles;
+        self.theme_overrides = config.theme;
+        self.profile_index = None;
+        if self.show_groups {
+            self.group_usage = self.sum_groups();
+        }
+        self.set_status("Config reloaded");
+    }
+
+    // The first press marks a process, the second opens it beside the one marked
+    fn mark_for_compare(&mut self) {
+        let Some(pid) = self.process_state.selected().and_then(|i| self.processes.get(i)).map(|info| info.pid) else { return };
+        match self.compare_mark.take() {
+            None => {
+                self.compare_mark = Some(pid);
+                self.set_status(format!("Marked {}; press M on another process to compare", pid));
+            }
+            Some(first) if first == pid => self.set_status("Comparison mark cleared"),
+            Some(first) => {
+                self.compare_pids = Some((first, pid));
+                self.input_mode = InputMode::Compare;
+            }
+        }
+    }
+
+    // Threads share their process's memory, so only processes are recorded
+    fn process_snapshot(&self) -> ProcessSnapshot {
+        self.system.processes().values()
+            .filter(|p| p.thread_kind() != Some(ThreadKind::Userland))
+            .map(|p| (p.pid(), self.process_label(p.pid(), p.name()), p.cpu_usage(), p.memory()))
+            .collect()
+    }
+
+    // The first press records a baseline; later presses show what changed since
+    fn capture_baseline(&mut self) {
+        if self.remote.is_some() {
+            self.set_status("Processes are not available from node-exporter");
+            return;
+        }
+        if self.baseline.is_none() {
+            self.baseline = Some((Instant::now(), self.process_snapshot()));
+            self.set_status("Baseline captured; press b again to see what changed");
+        } else {
+            self.baseline_current = Some(self.process_snapshot());
+            self.input_mode = InputMode::Baseline;
+        }
+    }
+
+    fn inspect_selected_process(&mut self) {
+        if let Some(i) = self.process_state.selected() {
+            if let Some(info) = self.processes.get(i) {
+                self.selected_pid = Some(info.pid);
+                self.input_mode = InputMode::Details;
+                self.details_scroll = 0;
+            }
+        }
+    }
+
+    // Scrolls the unwrapped command line, stopping with its last character in view
+    fn scroll_details(&mut self, right: bool) {
+        const STEP: usize = 8;
+        let len = self.selected_pid.and_then(|pid| self.system.process(pid)).map_or(0, |p| p.cmd().join(" ").chars().count());
+        self.details_scroll = if right { (self.details_scroll + STEP).min(len.saturating_sub(1)) } else { self.details_scroll.saturating_sub(STEP) };
+    }
+}
+
+// Link state and addresses per interface, as reported by getifaddrs(3)
+#[cfg(unix)]
+fn interface_addresses() -> HashMap<String, (bool, Vec<String>)> {
+    use std::ffi::CStr;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let mut result: HashMap<String, (bool, Vec<String>)> = HashMap::new();
+    let mut ifap: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut ifap) } != 0 {
+        return result;
+    }
+
+    let mut cursor = ifap;
+    while !cursor.is_null() {
+        let ifa = unsafe { &*cursor };
+        cursor = ifa.ifa_next;
+        if ifa.ifa_name.is_null() { continue; }
+
+        let name = unsafe { CStr::from_ptr(ifa.ifa_name) }.to_string_lossy().into_owned();
+        let flags = ifa.ifa_flags as libc::c_int;
+        let entry = result.entry(name).or_insert((false, Vec::new()));
+        entry.0 |= flags & libc::IFF_UP != 0 && flags & libc::IFF_RUNNING != 0;
+
+        if ifa.ifa_addr.is_null() { continue; }
+        match unsafe { (*ifa.ifa_addr).sa_family } as libc::c_int {
+            libc::AF_INET => {
+                let addr = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in) };
+                let prefix = if ifa.ifa_netmask.is_null() {
+                    32
+                } else {
+                    let mask = unsafe { &*(ifa.ifa_netmask as *const libc::sockaddr_in) };
+                    u32::from_be(mask.sin_addr.s_addr).count_ones()
+                };
+                entry.1.push(format!("{}/{}", Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)), prefix));
+            }
+            libc::AF_INET6 => {
+                let addr = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in6) };
+                let prefix = if ifa.ifa_netmask.is_null() {
+                    128
+                } else {
+                    let mask = unsafe { &*(ifa.ifa_netmask as *const libc::sockaddr_in6) };
+                    mask.sin6_addr.s6_addr.iter().map(|b| b.count_ones()).sum()
+                };
+                entry.1.push(format!("{}/{}", Ipv6Addr::from(addr.sin6_addr.s6_addr), prefix));
+            }
+            _ => {}
+        }
+    }
+
+    unsafe { libc::freeifaddrs(ifap) };
+    result
+}
+
+// System-wide context switch count since boot, from /proc/stat
+#[cfg(target_os = "linux")]
+fn context_switches() -> Option<u64> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    stat.lines().find_map(|line| line.strip_prefix("ctxt ")?.trim().parse().ok())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn context_switches() -> Option<u64> {
+    None
+}
+
+// Bytes of a process's memory sitting in swap, from VmSwap in /proc/<pid>/status.
+// Kernel threads have no such line.
+#[cfg(target_os = "linux")]
+fn process_swap(pid: Pid) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let line = status.lines().find_map(|line| line.strip_prefix("VmSwap:"))?;
+    let kb: u64 = line.split_whitespace().next()?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_swap(_pid: Pid) -> Option<u64> {
+    None
+}
+
+// User and system CPU time a process has used, from /proc/<pid>/stat
+#[cfg(target_os = "linux")]
+fn process_cpu_times(pid: Pid) -> Option<(Duration, Duration)> {
+    parse_cpu_times(&std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+#[cfg(target_os = "linux")]
+fn parse_cpu_times(stat: &str) -> Option<(Duration, Duration)> {
+    // The command name can contain spaces and parens, so count fields from the last ')'
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks_per_second <= 0 {
+        return None;
+    }
+    let ticks = |i: usize| -> Option<Duration> {
+        let ticks: u64 = fields.get(i)?.parse().ok()?;
+        Some(Duration::from_secs_f64(ticks as f64 / ticks_per_second as f64))
+    };
+    // utime and stime are fields 14 and 15, i.e. 11 and 12 after the command name
+    Some((ticks(11)?, ticks(12)?))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_cpu_times(_pid: Pid) -> Option<(Duration, Duration)> {
+    None
+}
+
+// Each thread's id, name and total CPU time, from /proc/<pid>/task/<tid>/stat
+#[cfg(target_os = "linux")]
+fn thread_cpu_times(pid: Pid) -> Vec<(u32, String, Duration)> {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/task", pid)) else { return Vec::new() };
+    entries.flatten().filter_map(|entry| {
+        let tid: u32 = entry.file_name().to_str()?.parse().ok()?;
+        let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+        let name = stat.split_once('(')?.1.rsplit_once(')')?.0.to_string();
+        let (user, system) = parse_cpu_times(&stat)?;
+        Some((tid, name, user + system))
+    }).collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn thread_cpu_times(_pid: Pid) -> Vec<(u32, String, Duration)> {
+    Vec::new()
+}
+
+fn process_cpu_time(pid: Pid) -> Option<Duration> {
+    process_cpu_times(pid).map(|(user, system)| user + system)
+}
+
+// Aggregate CPU time from the first line of /proc/stat, in clock ticks
+#[cfg(target_os = "linux")]
+fn cpu_times() -> Option<CpuTimes> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    let fields: Vec<f64> = stat.lines().next()?.strip_prefix("cpu ")?
+        .split_whitespace()
+        .filter_map(|field| field.parse().ok())
+        .collect();
+    // user nice system idle iowait irq softirq steal; guest time is already counted in user
+    let field = |i: usize| fields.get(i).copied().unwrap_or(0.0);
+    Some(CpuTimes {
+        user: field(0) + field(1),
+        system: field(2) + field(5) + field(6),
+        iowait: field(4),
+        steal: field(7),
+        total: (0..8).map(field).sum(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_times() -> Option<CpuTimes> {
+    None
+}
+
+// Inode identifying the network namespace of `/proc/<entry>`
+#[cfg(target_os = "linux")]
+fn net_namespace(entry: &str) -> Option<u64> {
+    let link = std::fs::read_link(format!("/proc/{}/ns/net", entry)).ok()?;
+    link.to_str()?.strip_prefix("net:[")?.strip_suffix(']')?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn net_namespace(_entry: &str) -> Option<u64> {
+    None
+}
+
+// Container a process runs in, from its cgroup path: the name Docker recorded for
+// it when readable, otherwise the short ID
+#[cfg(target_os = "linux")]
+fn process_container(pid: Pid) -> Option<String> {
+    let cgroup = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    let id = container_id(&cgroup)?;
+    let name = std::fs::read_to_string(format!("/var/lib/docker/containers/{}/config.v2.json", id)).ok()
+        .and_then(|config| Some(config.split_once("\"Name\":\"/")?.1.split_once('"')?.0.to_string()));
+    Some(name.unwrap_or_else(|| id[..12].to_string()))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_container(_pid: Pid) -> Option<String> {
+    None
+}
+
+// Full container ID in a cgroup path. Docker, containerd, CRI-O and Podman all name
+// a cgroup after the 64-hex-digit ID, either bare (`/docker/<id>`) or with a runtime
+// prefix (`docker-<id>.scope`, `cri-containerd-<id>.scope`, `libpod-<id>.scope`).
+#[cfg(target_os = "linux")]
+fn container_id(cgroup: &str) -> Option<String> {
+    cgroup.lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .flat_map(|path| path.rsplit('/'))
+        .map(|segment| {
+            let segment = segment.strip_suffix(".scope").unwrap_or(segment);
+            segment.rsplit_once('-').map_or(segment, |(_, id)| id)
+        })
+        .find(|id| id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit()))
+        .map(str::to_string)
+}
+
+// Sectors read and written so far per block device, from /proc/diskstats
+#[cfg(target_os = "linux")]
+fn disk_sectors() -> Option<HashMap<String, (u64, u64)>> {
+    let contents = std::fs::read_to_string("/proc/diskstats").ok()?;
+    Some(contents.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        Some((fields.get(2)?.to_string(), (fields.get(5)?.parse().ok()?, fields.get(9)?.parse().ok()?)))
+    }).collect())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn disk_sectors() -> Option<HashMap<String, (u64, u64)>> {
+    None
+}
+
+// Name /proc/diskstats uses for a device path. Symlinks such as /dev/mapper/vg-root
+// are resolved to the kernel's name for them (dm-0).
+#[cfg(target_os = "linux")]
+fn block_device_name(device: &Path) -> String {
+    let resolved = std::fs::canonicalize(device).unwrap_or_else(|_| device.to_path_buf());
+    resolved.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn block_device_name(_device: &Path) -> String {
+    String::new()
+}
+
+// Bytes per second with a unit suffix, short enough for a narrow column
+fn format_rate(bytes_per_second: u64, units: DiskUnits) -> String {
+    let suffixes = units.suffixes();
+    let mut value = bytes_per_second as f64;
+    let mut unit = 0;
+    while value >= units.base() && unit < suffixes.len() - 1 {
+        value /= units.base();
+        unit += 1;
+    }
+    if unit == 0 { format!("{}B", bytes_per_second) } else { format!("{:.1}{}", value, suffixes[unit]) }
+}
+
+// Total RX/TX bytes across non-loopback interfaces in a process's net namespace
+#[cfg(target_os = "linux")]
+fn namespace_net_totals(pid: &str) -> Option<(u64, u64)> {
+    let contents = std::fs::read_to_string(format!("/proc/{}/net/dev", pid)).ok()?;
+    let mut totals = (0, 0);
+    for line in contents.lines().skip(2) {
+        let (iface, counters) = line.split_once(':')?;
+        if iface.trim() == "lo" {
+            continue;
+        }
+        let fields: Vec<u64> = counters.split_whitespace().filter_map(|f| f.parse().ok()).collect();
+        if fields.len() >= 9 {
+            totals.0 += fields[0];
+            totals.1 += fields[8];
+        }
+    }
+    Some(totals)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn namespace_net_totals(_pid: &str) -> Option<(u64, u64)> {
+    None
+}
+
+// Maps socket inodes to the PID holding them open, from /proc/<pid>/fd
+#[cfg(target_os = "linux")]
+fn socket_owners() -> HashMap<u64, Pid> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = std::fs::read_dir("/proc") else { return owners };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else { continue };
+        // Other users' fds are unreadable without privileges; skip them quietly
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else { continue };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else { continue };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target.strip_prefix("socket:[").and_then(|t| t.strip_suffix(']')) {
+                if let Ok(inode) = inode.parse() {
+                    owners.insert(inode, Pid::from_u32(pid));
+                }
+            }
+        }
+    }
+    owners
+}
+
+// PIDs with an open fd whose link target passes `wanted`. Processes whose fds we
+// can't read are left out, so without privileges only our own processes show up.
+#[cfg(target_os = "linux")]
+fn pids_with_fd(wanted: impl Fn(&str) -> bool) -> HashSet<Pid> {
+    let mut pids = HashSet::new();
+    let Ok(entries) = std::fs::read_dir("/proc") else { return pids };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else { continue };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else { continue };
+        let found = fds.flatten().any(|fd| std::fs::read_link(fd.path()).is_ok_and(|target| wanted(&target.to_string_lossy())));
+        if found {
+            pids.insert(Pid::from_u32(pid));
+        }
+    }
+    pids
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pids_with_fd(_wanted: impl Fn(&str) -> bool) -> HashSet<Pid> {
+    HashSet::new()
+}
+
+// Inodes of TCP/UDP sockets bound locally to `port`, in any state
+#[cfg(target_os = "linux")]
+fn socket_inodes_on_port(port: u16) -> HashSet<u64> {
+    let mut inodes = HashSet::new();
+    for file in ["/proc/net/tcp", "/proc/net/tcp6", "/proc/net/udp", "/proc/net/udp6"] {
+        let Ok(contents) = std::fs::read_to_string(file) else { continue };
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || parse_proc_address(fields[1]).is_none_or(|(_, local)| local != port) {
+                continue;
+            }
+            if let Ok(inode) = fields[9].parse() {
+                inodes.insert(inode);
+            }
+        }
+    }
+    inodes
+}
+
+#[cfg(not(target_os = "linux"))]
+fn socket_inodes_on_port(_port: u16) -> HashSet<u64> {
+    HashSet::new()
+}
+
+// Decodes a /proc/net address such as "0100007F:1F90" into (ip, port)
+#[cfg(target_os = "linux")]
+fn parse_proc_address(field: &str) -> Option<(String, u16)> {
+    let (ip_hex, port_hex) = field.split_once(':')?;
+    let port = u16::from_str_radix(port_hex, 16).ok()?;
+    // The kernel prints each 32-bit word of the address in host byte order
+    let mut bytes = Vec::with_capacity(16);
+    for chunk in ip_hex.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    let ip = match bytes.len() {
+        4 => std::net::Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string(),
+        16 => std::net::Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?).to_string(),
+        _ => return None,
+    };
+    Some((ip, port))
+}
+
+// Open connections per PID: TCP sockets in any state but LISTEN, and connected UDP
+// sockets. PIDs whose fds can't be read are left out rather than counted as zero.
+#[cfg(target_os = "linux")]
+fn connection_counts() -> HashMap<Pid, usize> {
+    const TCP_LISTEN: &str = "0A";
+    const UDP_CONNECTED: &str = "01";
+
+    let mut connections = HashSet::new();
+    for (file, is_connection) in [
+        ("/proc/net/tcp", (|state| state != TCP_LISTEN) as fn(&str) -> bool),
+        ("/proc/net/tcp6", |state| state != TCP_LISTEN),
+        ("/proc/net/udp", |state| state == UDP_CONNECTED),
+        ("/proc/net/udp6", |state| state == UDP_CONNECTED),
+    ] {
+        let Ok(contents) = std::fs::read_to_string(file) else { continue };
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || !is_connection(fields[3]) {
+                continue;
+            }
+            if let Ok(inode) = fields[9].parse::<u64>() {
+                connections.insert(inode);
+            }
+        }
+    }
+
+    let mut counts = HashMap::new();
+    let Ok(entries) = std::fs::read_dir("/proc") else { return counts };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else { continue };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else { continue };
+        let count = fds.flatten()
+            .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+            .filter_map(|target| target.to_str()?.strip_prefix("socket:[")?.strip_suffix(']')?.parse::<u64>().ok())
+            .filter(|inode| connections.contains(inode))
+            .count();
+        counts.insert(Pid::from_u32(pid), count);
+    }
+    counts
+}
+
+#[cfg(not(target_os = "linux"))]
+fn connection_counts() -> HashMap<Pid, usize> {
+    HashMap::new()
+}
+
+// Sockets in LISTEN state (TCP) or bound and unconnected (UDP), sorted by port
+#[cfg(target_os = "linux")]
+fn listening_ports() -> Vec<ListeningPort> {
+    const TCP_LISTEN: &str = "0A";
+    const UDP_UNCONNECTED: &str = "07";
+
+    let owners = socket_owners();
+    let mut ports = Vec::new();
+    for (proto, file, state) in [
+        ("tcp", "/proc/net/tcp", TCP_LISTEN),
+        ("tcp6", "/proc/net/tcp6", TCP_LISTEN),
+        ("udp", "/proc/net/udp", UDP_UNCONNECTED),
+        ("udp6", "/proc/net/udp6", UDP_UNCONNECTED),
+    ] {
+        let Ok(contents) = std::fs::read_to_string(file) else { continue };
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != state {
+                continue;
+            }
+            let Some((address, port)) = parse_proc_address(fields[1]) else { continue };
+            let pid = fields[9].parse::<u64>().ok().and_then(|inode| owners.get(&inode).copied());
+            ports.push(ListeningPort { proto, address, port, pid });
+        }
+    }
+    ports.sort_by(|a, b| a.port.cmp(&b.port).then(a.proto.cmp(b.proto)));
+    ports
+}
+
+#[cfg(not(target_os = "linux"))]
+fn listening_ports() -> Vec<ListeningPort> {
+    Vec::new()
+}
+
+fn unfocused_tick_rate(config: &Config) -> Option<Duration> {
+    (config.unfocused_tick_rate_ms > 0).then(|| Duration::from_millis(config.unfocused_tick_rate_ms))
+}
+
+// Physical core index for each logical CPU, so hyperthread siblings share an index
+#[cfg(target_os = "linux")]
+fn physical_core_ids(cpu_count: usize) -> Option<Vec<usize>> {
+    let read_id = |cpu: usize, file: &str| -> Option<u32> {
+        std::fs::read_to_string(format!("/sys/devices/system/cpu/cpu{}/topology/{}", cpu, file)).ok()?.trim().parse().ok()
+    };
+    let keys: Vec<(u32, u32)> = (0..cpu_count)
+        .map(|cpu| Some((read_id(cpu, "physical_package_id")?, read_id(cpu, "core_id")?)))
+        .collect::<Option<_>>()?;
+    let mut unique = keys.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    Some(keys.iter().map(|key| unique.binary_search(key).unwrap_or(0)).collect())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn physical_core_ids(_cpu_count: usize) -> Option<Vec<usize>> {
+    None
+}
+
+// Cores the process is allowed to run on, via sched_getaffinity(2)
+#[cfg(target_os = "linux")]
+fn get_affinity(pid: Pid) -> Option<Vec<usize>> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    if unsafe { libc::sched_getaffinity(pid.as_u32() as libc::pid_t, size, &mut set) } != 0 {
+        return None;
+    }
+    Some((0..libc::CPU_SETSIZE as usize).filter(|&i| unsafe { libc::CPU_ISSET(i, &set) }).collect())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_affinity(_pid: Pid) -> Option<Vec<usize>> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn set_affinity(pid: Pid, cores: &[usize]) -> io::Result<()> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &core in cores {
+        unsafe { libc::CPU_SET(core, &mut set) };
+    }
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    if unsafe { libc::sched_setaffinity(pid.as_u32() as libc::pid_t, size, &set) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_affinity(_pid: Pid, _cores: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "CPU affinity is only supported on Linux"))
+}
+
+// Parses a core list such as "0,2-3" into core indices below `core_count`
+fn parse_core_list(input: &str, core_count: usize) -> Option<Vec<usize>> {
+    let mut cores = Vec::new();
+    for part in input.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((a, b)) => (a.trim().parse::<usize>().ok()?, b.trim().parse::<usize>().ok()?),
+            None => {
+                let core = part.parse::<usize>().ok()?;
+                (core, core)
+            }
+        };
+        if start > end || end >= core_count {
+            return None;
+        }
+        cores.extend(start..=end);
+    }
+    cores.sort_unstable();
+    cores.dedup();
+    if cores.is_empty() { None } else { Some(cores) }
+}
+
+// Formats core indices compactly, collapsing runs: [0, 2, 3] -> "0,2-3"
+fn format_core_list(cores: &[usize]) -> String {
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < cores.len() {
+        let start = cores[i];
+        while i + 1 < cores.len() && cores[i + 1] == cores[i] + 1 {
+            i += 1;
+        }
+        if cores[i] == start {
+            parts.push(start.to_string());
+        } else {
+            parts.push(format!("{}-{}", start, cores[i]));
+        }
+        i += 1;
+    }
+    parts.join(",")
+}
+
+// Percentage of inodes in use on the filesystem mounted at `path`
+#[cfg(unix)]
+fn inode_usage(path: &Path) -> Option<u16> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let total = stat.f_files as u64;
+    let free = stat.f_ffree as u64;
+    // Filesystems without a fixed inode table (btrfs, some FUSE mounts) report 0
+    if total == 0 {
+        return None;
+    }
+    Some(((total - free) as f64 / total as f64 * 100.0) as u16)
+}
+
+#[cfg(not(unix))]
+fn inode_usage(_path: &Path) -> Option<u16> {
+    None
+}
+
+#[cfg(not(unix))]
+fn interface_addresses() -> HashMap<String, (bool, Vec<String>)> {
+    HashMap::new()
+}
+
+// Local wall-clock time for a Unix timestamp, e.g. "2024-01-15 09:32"
+fn format_timestamp(timestamp: u64) -> String {
+    Local.timestamp_opt(timestamp as i64, 0)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn select_next(state: &mut TableState, len: usize) {
+    if len == 0 { return; }
+    let i = match state.selected() {
+        Some(i) => if i >= len - 1 { 0 } else { i + 1 },
+        None => 0,
+    };
+    state.select(Some(i));
+}
+
+fn select_previous(state: &mut TableState, len: usize) {
+    if len == 0 { return; }
+    let i = match state.selected() {
+        Some(i) => if i == 0 { len - 1 } else { i - 1 },
+        None => 0,
+    };
+    state.select(Some(i));
+}
+
+// --check: one measurement and a one-line summary, e.g. for cron or CI. The exit
+// code follows the Nagios convention (0 OK, 1 WARNING, 2 CRITICAL), judged on the
+// higher of CPU and memory usage.
+fn run_check(config: &Config, keymap: HashMap<KeyCode, Action>, args: &Args) -> i32 {
+    let mut app = App::new(config, keymap, args);
+    // The exit code is the alert here; the cooldown wouldn't outlive this run anyway
+    app.alert_command = None;
+    // CPU usage is measured between two refreshes
+    app.on_tick();
+    std::thread::sleep(Duration::from_millis(500));
+    app.on_tick();
+
+    let cpu = *app.cpu_history.back().unwrap_or(&0);
+    let mem = *app.mem_history.back().unwrap_or(&0);
+    let worst = cpu.max(mem);
+    let (label, code) = if args.crit.is_some_and(|crit| worst >= crit) {
+        ("CRITICAL", 2)
+    } else if args.warn.is_some_and(|warn| worst >= warn) {
+        ("WARNING", 1)
+    } else {
+        ("OK", 0)
+    };
+    println!("{} - {}: CPU {}%, memory {}%, swap {}%", label, app.host_name(), cpu, mem, app.swap_percent);
+    code
+}
+
+// Developer aid for --stress: each core swings between idle and busy on its own
+// sine wave, and memory climbs by 32 MB a second to 256 MB before being released.
+// The threads are detached and die with the process.
+fn spawn_stress_load() {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    for core in 0..cores {
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            loop {
+                let phase = start.elapsed().as_secs_f64() / 5.0 + core as f64;
+                let busy = Duration::from_millis((50.0 + 50.0 * phase.sin()) as u64);
+                let busy_start = Instant::now();
+                while busy_start.elapsed() < busy {
+                    std::hint::black_box(busy_start.elapsed());
+                }
+                std::thread::sleep(Duration::from_millis(100).saturating_sub(busy));
+            }
+        });
+    }
+    std::thread::spawn(|| loop {
+        let mut blocks = Vec::new();
+        for _ in 0..8 {
+            blocks.push(vec![1u8; 32 * 1024 * 1024]);
+            std::thread::sleep(Duration::from_secs(1));
+        }
+        drop(blocks);
+        std::thread::sleep(Duration::from_secs(4));
+    });
+}
+
+fn main() -> Result<()> {
+    let config = Config::load()?;
+    let keymap = config.keymap()?;
+    let args = Args::parse()?;
+    if let Some(pid) = args.watch {
+        if !System::new().refresh_process(pid) {
+            anyhow::bail!("no process with PID {}", pid);
+        }
+    }
+    if args.stress {
+        spawn_stress_load();
+    }
+
+    if args.check {
+        std::process::exit(run_check(&config, keymap, &args));
+    }
+
+    enable_raw_mode()?;
+    let light_background = detect_light_background();
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableFocusChange)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let mut app = App::new(&config, keymap, &args);
+    if light_background == Some(true) {
+        app.current_theme = ThemePreset::Light;
+    }
+    let mut last_tick = Instant::now();
+
+    loop {
+        if app.needs_redraw {
+            let frame = terminal.draw(|f| ui(f, &mut app))?;
+            let snapshot = app.snapshot_requested.then(|| save_snapshot(frame.buffer));
+            app.mark_drawn();
+            if let Some(saved) = snapshot {
+                app.snapshot_requested = false;
+                match saved {
+                    Ok(path) => app.set_status(format!("Snapshot saved to {}", path.display())),
+                    Err(err) => app.set_status(format!("Snapshot failed: {:#}", err)),
+                }
+                app.needs_redraw = true;
+            }
+        }
+
+        // While paused, still wake now and then so the status line can expire
+        let tick_rate = app.current_tick_rate();
+        let timeout = tick_rate.map_or(Duration::from_secs(1), |rate| rate.saturating_sub(last_tick.elapsed()));
+
+        if crossterm::event::poll(timeout)? {
+            let event = event::read()?;
+            app.needs_redraw = true;
+            if let Event::FocusGained | Event::FocusLost = event {
+                app.terminal_focused = matches!(event, Event::FocusGained);
+            }
+            if let Event::Key(key) = event {
+                if key.kind == KeyEventKind::Press {
+                    match app.input_mode {
+                        InputMode::Normal => match app.keymap.get(&key.code) {
+                            Some(Action::Quit) => app.should_quit = true,
+                            Some(Action::Next) => app.next_item(),
+                            Some(Action::Previous) => app.previous_item(),
+                            Some(Action::Kill) => app.kill_selected_process(),
+                            Some(Action::Filter) => {
+                                app.input_mode = InputMode::Editing;
+                                app.focused_panel = FocusedPanel::Processes;
+                                app.process_state.select(Some(0)); 
+                            }
+                            Some(Action::Inspect) => app.inspect_selected_process(),
+                            Some(Action::Theme) => {
+                                app.current_theme = app.current_theme.next();
+                            }
+                            None => match key.code {
+                                KeyCode::Tab => app.focused_panel = app.focused_panel.next(),
+                                KeyCode::BackTab => app.focused_panel = app.focused_panel.previous(),
+                                KeyCode::Char('e') => app.open_command_menu(),
+                                KeyCode::Char(':') | KeyCode::Char('#') => {
+                                    app.input_mode = InputMode::PidEntry;
+                                    app.pid_input.clear();
+                                }
+                                KeyCode::Char('J') => app.cycle_profile(),
+                                KeyCode::Char('+') => {
+                                    app.input_mode = InputMode::ProfileName;
+                                    app.profile_name_input.clear();
+                                }
+                                KeyCode::Char('=') => {
+                                    app.input_mode = InputMode::TickRateEntry;
+                                    app.tick_rate_input.clear();
+                                }
+                                KeyCode::Char('c') => app.reset_history(),
+                                KeyCode::Char('f') => app.show_filter_bar = !app.show_filter_bar,
+                                KeyCode::Char('s') => app.details_sidebar = !app.details_sidebar,
+                                KeyCode::Char('w') => app.snapshot_requested = true,
+                                KeyCode::Char('D') => app.export_process_tree(),
+                                KeyCode::Char('W') => app.export_markdown(),
+                                KeyCode::Char('E') => app.export_theme(),
+                                KeyCode::Char('G') => app.show_gauges = !app.show_gauges,
+                                KeyCode::Char('L') => app.toggle_full_disks(),
+                                KeyCode::Char('O') => app.portrait = !app.portrait,
+                                KeyCode::Char('F') => app.process_fullscreen = !app.process_fullscreen,
+                                KeyCode::Char('u') => app.toggle_cpu_time(),
+                                KeyCode::Char('T') => app.toggle_threads(),
+                                KeyCode::Char('A') => {
+                                    app.anonymize = !app.anonymize;
+                                    app.assign_process_aliases();
+                                }
+                                KeyCode::Char('P') => {
+                                    app.input_mode = InputMode::Editing;
+                                    app.focused_panel = FocusedPanel::Processes;
+                                    app.search_query = "path:".to_string();
+                                app.apply_search(false);
+                                    app.process_state.select(Some(0));
+                                }
+                                KeyCode::Char('p') => app.toggle_ports(),
+                                KeyCode::Char('y') => app.toggle_groups(),
+                                KeyCode::Char('.') => app.toggle_memory_breakdown(),
+                                KeyCode::Char('-') => app.gridded_graphs = !app.gridded_graphs,
+                                KeyCode::Char('@') => app.toggle_real_time_axis(),
+                                KeyCode::Char('N') => app.net_cumulative = !app.net_cumulative,
+                                KeyCode::Char('o') => app.open_config_dir(),
+                                KeyCode::Char('C') => app.toggle_connection_sort(),
+                                KeyCode::Char('b') => app.capture_baseline(),
+                                KeyCode::Char('l') => app.toggle_quiet(),
+                                KeyCode::Char('*') => app.toggle_auto_follow(),
+                                KeyCode::Char('I') => app.toggle_containers(),
+                                KeyCode::Char('X') => app.toggle_swap_column(),
+                                KeyCode::Char('U') => app.cpu_gauge_max_core = !app.cpu_gauge_max_core,
+                                KeyCode::Char('Y') => app.show_table_header = !app.show_table_header,
+                                KeyCode::Char('S') => app.density = app.density.next(),
+                                KeyCode::Char('B') => app.toggle_busiest_cores(),
+                                KeyCode::Char('Z') => app.mark_abnormal = !app.mark_abnormal,
+                                KeyCode::Char('!') => app.toggle_watchlist(),
+                                KeyCode::Char('d') => app.show_start_column = !app.show_start_column,
+                                KeyCode::Char('z') => app.freeze_list = !app.freeze_list,
+                                KeyCode::Char('m') => app.toggle_memory_sort(),
+                                KeyCode::Char('r') => app.toggle_trend_sort(),
+                                KeyCode::Char('i') => app.invert_gauges = !app.invert_gauges,
+                                KeyCode::Char('g') => app.cpu_graph = app.cpu_graph.next(),
+                                KeyCode::Char('R') => app.granularity = app.granularity.next(),
+                                KeyCode::Char('h') => app.core_heatmap = !app.core_heatmap,
+                                KeyCode::Char('H') => app.show_core_temps = !app.show_core_temps,
+                                KeyCode::Char(',') => app.group_digits = !app.group_digits,
+                                KeyCode::Char('M') => app.mark_for_compare(),
+                                KeyCode::F(5) => app.reload_config(),
+                                KeyCode::Char('v') => app.name_display = app.name_display.next(),
+                                KeyCode::Char('V') => {
+                                    app.name_length = app.name_length.next();
+                                    app.set_status(format!("Process names: {}", app.name_length.label()));
+                                }
+                                KeyCode::Char('a') => app.acknowledge_alerts(),
+                                KeyCode::Char('[') => app.change_process_limit(false),
+                                KeyCode::Char(']') => app.change_process_limit(true),
+                                KeyCode::Char('n') => {
+                                    if cfg!(target_os = "linux") {
+                                        app.show_process_net = !app.show_process_net;
+                                    } else {
+                                        app.set_status("Network namespace traffic is only supported on Linux");
+                                    }
+                                }
+                                _ => {}
+                            },
+                        },
+                        InputMode::Editing => match key.code {
+                            KeyCode::Enter | KeyCode::Esc => {
+                                app.input_mode = InputMode::Normal;
+                                app.apply_search(true);
+                            }
+                            KeyCode::Backspace => {
+                                app.search_query.pop();
+                                app.apply_search(false);
+                            }
+                            KeyCode::Char(c) => {
+                                app.search_query.push(c);
+                                app.apply_search(false);
+                            }
+                            KeyCode::Tab => {
+                                app.search_case_sensitive = !app.search_case_sensitive;
+                                app.apply_search(false);
+                            }
+                            _ => {}
+                        },
+                        InputMode::Details => match key.code {
+                            KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace => {
+                                app.input_mode = InputMode::Normal;
+                                app.selected_pid = None;
+                            }
+                            KeyCode::Char('a') => app.edit_affinity(),
+                            KeyCode::Char('w') => app.details_wrap = !app.details_wrap,
+                            KeyCode::Char('c') => app.copy_command(),
+                            KeyCode::Left if !app.details_wrap => app.scroll_details(false),
+                            KeyCode::Right if !app.details_wrap => app.scroll_details(true),
+                            KeyCode::Home => app.details_scroll = 0,
+                            // Collapse the popup into the sidebar, which follows the selection
+                            KeyCode::Char('s') => {
+                                app.input_mode = InputMode::Normal;
+                                app.selected_pid = None;
+                                app.details_sidebar = true;
+                            }
+                            _ => {}
+                        },
+                        InputMode::Baseline => match key.code {
+                            KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace => {
+                                app.input_mode = InputMode::Normal;
+                                app.baseline_current = None;
+                            }
+                            // The snapshot just compared becomes the new baseline
+                            KeyCode::Char('b') => {
+                                app.input_mode = InputMode::Normal;
+                                app.baseline = app.baseline_current.take().map(|current| (Instant::now(), current));
+                                app.set_status("New baseline captured");
+                            }
+                            _ => {}
+                        },
+                        InputMode::Compare => match key.code {
+                            KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace => {
+                                app.input_mode = InputMode::Normal;
+                                app.compare_pids = None;
+                            }
+                            _ => {}
+                        },
+                        InputMode::Commands => match key.code {
+                            KeyCode::Esc => app.input_mode = InputMode::Normal,
+                            KeyCode::Char(c @ '1'..='9') => app.choose_command(c as usize - '1' as usize),
+                            _ => {}
+                        },
+                        InputMode::ProfileName => match key.code {
+                            KeyCode::Enter => app.save_profile(),
+                            KeyCode::Esc => app.input_mode = InputMode::Normal,
+                            KeyCode::Backspace => {
+                                app.profile_name_input.pop();
+                            }
+                            KeyCode::Char(c) => {
+                                app.profile_name_input.push(c);
+                            }
+                            _ => {}
+                        },
+                        InputMode::TickRateEntry => match key.code {
+                            KeyCode::Enter => app.apply_tick_rate_input(),
+                            KeyCode::Esc => app.input_mode = InputMode::Normal,
+                            KeyCode::Backspace => {
+                                app.tick_rate_input.pop();
+                            }
+                            KeyCode::Char(c) if c.is_ascii_digit() => {
+                                app.tick_rate_input.push(c);
+                            }
+                            _ => {}
+                        },
+                        InputMode::PidEntry => match key.code {
+                            KeyCode::Enter => app.jump_to_pid(),
+                            KeyCode::Esc => app.input_mode = InputMode::Normal,
+                            KeyCode::Backspace => {
+                                app.pid_input.pop();
+                            }
+                            KeyCode::Char(c) if c.is_ascii_digit() => {
+                                app.pid_input.push(c);
+                            }
+                            _ => {}
+                        },
+                        InputMode::Affinity => match key.code {
+                            KeyCode::Enter => app.apply_affinity(),
+                            KeyCode::Esc => app.input_mode = InputMode::Details,
+                            KeyCode::Backspace => {
+                                app.affinity_input.pop();
+                            }
+                            KeyCode::Char(c) if c.is_ascii_digit() || c == ',' || c == '-' => {
+                                app.affinity_input.push(c);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some((command, pid)) = app.pending_command.take() {
+            let outcome = run_external_command(&mut terminal, &command, pid)?;
+            app.set_status(outcome);
+        }
+
+        if app.status.as_ref().is_some_and(|(_, at)| at.elapsed() >= STATUS_TIMEOUT) {
+            app.status = None;
+            app.needs_redraw = true;
+        }
+
+        if tick_rate.is_some_and(|rate| last_tick.elapsed() >= rate) {
+            app.on_tick();
+            last_tick = Instant::now();
+        }
+
+        if app.should_quit {
+            break;
+        }
+    }
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        DisableFocusChange,
+        LeaveAlternateScreen
+    )?;
+    terminal.show_cursor()?;
+
+    if let Some(pid) = app.watch_pid.filter(|_| app.watched_exited) {
+        println!("Process {} exited", pid);
+        std::process::exit(WATCH_EXIT_CODE);
+    }
+    Ok(())
+}
+
+// Whether the terminal has a light background, from $COLORFGBG or else by asking the
+// terminal with OSC 11; None if neither answers. Needs raw mode for the query.
+fn detect_light_background() -> Option<bool> {
+    // "fg;bg" (sometimes "fg;default;bg") with ANSI indices; 7 and 9-15 are light
+    if let Some(bg) = std::env::var("COLORFGBG").ok().and_then(|v| v.rsplit(';').next()?.parse::<u8>().ok()) {
+        return Some(bg == 7 || bg > 8);
+    }
+    let reply = query_background_color()?;
+    // Reply is `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` with 1-4 hex digits per channel
+    let rgb = reply.split("rgb:").nth(1)?;
+    let channels: Vec<f64> = rgb
+        .split('/')
+        .take(3)
+        .map(|c| {
+            let digits: String = c.chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+            let max = 16f64.powi(digits.len() as i32) - 1.0;
+            u32::from_str_radix(&digits, 16).ok().map(|v| v as f64 / max)
+        })
+        .collect::<Option<_>>()?;
+    let [r, g, b] = channels[..] else { return None };
+    Some(0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5)
+}
+
+#[cfg(unix)]
+fn query_background_color() -> Option<String> {
+    use std::io::Write;
+
+    if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+        return None;
+    }
+    let mut stdout = io::stdout();
+    stdout.write_all(b"\x1b]11;?\x07").ok()?;
+    stdout.flush().ok()?;
+
+    // Terminals that don't support the query never answer, so give up quickly
+    let deadline = Instant::now() + Duration::from_millis(100);
+    let mut reply = Vec::new();
+    while !(reply.ends_with(b"\x07") || reply.ends_with(b"\x1b\\")) {
+        let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as libc::c_int;
+        let mut fd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+        if remaining == 0 || unsafe { libc::poll(&mut fd, 1, remaining) } <= 0 {
+            return None;
+        }
+        let mut byte = 0u8;
+        if unsafe { libc::read(libc::STDIN_FILENO, &mut byte as *mut u8 as *mut libc::c_void, 1) } != 1 {
+            return None;
+        }
+        reply.push(byte);
+    }
+    String::from_utf8(reply).ok()
+}
+
+#[cfg(not(unix))]
+fn query_background_color() -> Option<String> {
+    None
+}
+
+// Writes a rendered frame to the working directory as ANSI-coloured text; `cat` or
+// `less -R` replays it with the original colours
+fn save_snapshot(buffer: &Buffer) -> Result<PathBuf> {
+    const ATTRIBUTES: [(Modifier, Attribute); 6] = [
+        (Modifier::BOLD, Attribute::Bold),
+        (Modifier::DIM, Attribute::Dim),
+        (Modifier::ITALIC, Attribute::Italic),
+        (Modifier::UNDERLINED, Attribute::Underlined),
+        (Modifier::REVERSED, Attribute::Reverse),
+        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+    ];
+
+    let mut out = Vec::new();
+    for y in buffer.area.top()..buffer.area.bottom() {
+        let mut style = None;
+        let mut skip = 0;
+        for x in buffer.area.left()..buffer.area.right() {
+            // Wide characters cover the cells after them
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let cell = &buffer[(x, y)];
+            if style != Some((cell.fg, cell.bg, cell.modifier)) {
+                queue!(out, SetAttribute(Attribute::Reset), SetForegroundColor(cell.fg.into()), SetBackgroundColor(cell.bg.into()))?;
+                for (modifier, attribute) in ATTRIBUTES {
+                    if cell.modifier.contains(modifier) {
+                        queue!(out, SetAttribute(attribute))?;
+                    }
+                }
+                style = Some((cell.fg, cell.bg, cell.modifier));
+            }
+            out.extend_from_slice(cell.symbol().as_bytes());
+            skip = Span::raw(cell.symbol()).width().saturating_sub(1);
+        }
+        queue!(out, SetAttribute(Attribute::Reset))?;
+        out.push(b'\n');
+    }
+
+    let path = PathBuf::from(format!("term-dash-{}.ans", Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, out).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+// Writes the process list as a GitHub-flavoured markdown table under a one-line
+// system summary, for pasting into tickets and chat
+fn save_markdown(app: &App) -> Result<PathBuf> {
+    let mut out = format!(
+        "### {} at {}\n\nCPU {}% · Memory {}% · Swap {}%\n\n| PID | Name | CPU | Memory | MEM% |\n|---:|---|---:|---:|---:|\n",
+        app.host_name(),
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        app.cpu_history.back().unwrap_or(&0),
+        app.mem_history.back().unwrap_or(&0),
+        app.swap_percent,
+    );
+    for info in app.processes.iter().filter(|info| !app.vanished_pids.contains(&info.pid)) {
+        let name = app.display_name(info, usize::MAX);
+        out.push_str(&format!(
+            "| {} | {} | {:.1}% | {:.1} MB | {:.1}% |\n",
+            info.pid,
+            name.replace('|', "\\|"),
+            info.cpu,
+            info.mem as f64 / 1_048_576.0,
+            info.mem_percent,
+        ));
+    }
+
+    let path = PathBuf::from(format!("term-dash-{}.md", Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, out).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn append_to_config(text: &str) -> Result<PathBuf> {
+    use std::io::Write;
+    let path = Config::path().context("no config directory on this platform")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    file.write_all(text.as_bytes()).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+// Writes the colours on screen as a `[theme]` snippet, ready to paste into
+// config.toml and tweak
+fn save_theme(app: &App) -> Result<PathBuf> {
+    let out = format!("# term-dash {} theme\n{}", app.current_theme.name(), app.theme().to_toml());
+    let path = PathBuf::from(format!("term-dash-{}-theme.toml", Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, out).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+// Writes the process tree to the working directory as Graphviz DOT, one node per
+// process labelled with its PID and name; render it with `dot -Tsvg`
+fn save_process_tree(app: &App) -> Result<PathBuf> {
+    // Linux lists threads as processes too; they would bury the tree
+    let mut processes: Vec<&Process> = app.system.processes().values().filter(|p| p.thread_kind() != Some(ThreadKind::Userland)).collect();
+    processes.sort_by_key(|p| p.pid());
+    let pids: HashSet<Pid> = processes.iter().map(|p| p.pid()).collect();
+
+    let mut out = String::from("digraph processes {\n    node [shape=box];\n");
+    for process in &processes {
+        let name = app.process_label(process.pid(), process.name()).replace('\\', "\\\\").replace('"', "\\\"");
+        out.push_str(&format!("    {} [label=\"{} {}\"];\n", process.pid(), process.pid(), name));
+    }
+    for process in &processes {
+        if let Some(parent) = process.parent().filter(|parent| pids.contains(parent)) {
+            out.push_str(&format!("    {} -> {};\n", parent, process.pid()));
+        }
+    }
+    out.push_str("}\n");
+
+    let path = PathBuf::from(format!("term-dash-{}.dot", Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, out).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+// Hands the terminal over to `command` and restores the dashboard once it exits
+fn run_external_command(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, command: &str, pid: Pid) -> Result<String> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    println!("$ {}", command);
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("TERM_DASH_PID", pid.to_string())
+        .status();
+    println!("\n[Press Enter to return to term-dash]");
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    terminal.clear()?;
+
+    Ok(match status {
+        Ok(status) => format!("'{}' exited with {}", command, status),
+        Err(e) => format!("Failed to run '{}': {}", command, e),
+    })
+}
+
+// Hands a directory to the desktop's file manager. Output is discarded so it can't
+// draw over the dashboard; false when there is no desktop to ask, e.g. over SSH.
+fn open_in_file_manager(dir: &Path) -> bool {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else if std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        "xdg-open"
+    } else {
+        return false;
+    };
+    std::process::Command::new(opener)
+        .arg(dir)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|mut child| std::thread::spawn(move || child.wait())) // Reaped so it doesn't linger as a zombie
+        .is_ok()
+}
+
+// Processes that appeared, disappeared, grew in memory or changed CPU% between two
+// snapshots. A PID reused by a different program counts as one gone and one new.
+fn baseline_diff(theme: &Theme, age: Duration, baseline: &ProcessSnapshot, current: &ProcessSnapshot) -> Vec<Line<'static>> {
+    let before: HashMap<(Pid, &str), (f32, u64)> = baseline.iter().map(|(pid, name, cpu, mem)| ((*pid, name.as_str()), (*cpu, *mem))).collect();
+    let after: HashMap<(Pid, &str), (f32, u64)> = current.iter().map(|(pid, name, cpu, mem)| ((*pid, name.as_str()), (*cpu, *mem))).collect();
+    let mb = |bytes: u64| bytes as f64 / 1_048_576.0;
+
+    let mut appeared: Vec<_> = current.iter().filter(|(pid, name, _, _)| !before.contains_key(&(*pid, name.as_str()))).collect();
+    appeared.sort_by_key(|(_, _, _, mem)| std::cmp::Reverse(*mem));
+    let mut disappeared: Vec<_> = baseline.iter().filter(|(pid, name, _, _)| !after.contains_key(&(*pid, name.as_str()))).collect();
+    disappeared.sort_by_key(|(_, _, _, mem)| std::cmp::Reverse(*mem));
+    // (pid, name, before, after) for processes present in both
+    let both: Vec<_> = current.iter()
+        .filter_map(|(pid, name, cpu, mem)| Some((*pid, name.as_str(), *before.get(&(*pid, name.as_str()))?, (*cpu, *mem))))
+        .collect();
+    let mut grew: Vec<_> = both.iter().filter(|(_, _, old, new)| new.1 > old.1).collect();
+    grew.sort_by_key(|(_, _, old, new)| std::cmp::Reverse(new.1 - old.1));
+    let mut cpu_changed: Vec<_> = both.iter().filter(|(_, _, old, new)| (new.0 - old.0).abs() >= 1.0).collect();
+    cpu_changed.sort_by(|a, b| (b.3.0 - b.2.0).abs().partial_cmp(&(a.3.0 - a.2.0).abs()).unwrap_or(std::cmp::Ordering::Equal));
+
+    let secs = age.as_secs();
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Baseline taken {}m {:02}s ago · {} → {} processes", secs / 60, secs % 60, baseline.len(), current.len()),
+            Style::default().fg(theme.text),
+        )),
+    ];
+    let mut section = |title: String, rows: Vec<String>| {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(title, Style::default().fg(theme.border).add_modifier(Modifier::BOLD))));
+        if rows.is_empty() {
+            lines.push(Line::from(Span::styled("  none", Style::default().fg(Color::DarkGray))));
+        }
+        lines.extend(rows.into_iter().map(|row| Line::from(Span::styled(row, Style::default().fg(theme.text)))));
+    };
+    section(
+        format!("Appeared ({})", appeared.len()),
+        appeared.iter().take(BASELINE_DIFF_ROWS).map(|(pid, name, cpu, mem)| format!("  {:<7} {:<24} {:>6.1}% {:>9.1} MB", pid.as_u32(), name, cpu, mb(*mem))).collect(),
+    );
+    section(
+        format!("Disappeared ({})", disappeared.len()),
+        disappeared.iter().take(BASELINE_DIFF_ROWS).map(|(pid, name, cpu, mem)| format!("  {:<7} {:<24} {:>6.1}% {:>9.1} MB", pid.as_u32(), name, cpu, mb(*mem))).collect(),
+    );
+    section(
+        format!("Memory grew ({})", grew.len()),
+        grew.iter().take(BASELINE_DIFF_ROWS).map(|(pid, name, old, new)| format!("  {:<7} {:<24} {:>+9.1} MB ({:.1} → {:.1} MB)", pid.as_u32(), name, mb(new.1 - old.1), mb(old.1), mb(new.1))).collect(),
+    );
+    section(
+        format!("CPU changed ({})", cpu_changed.len()),
+        cpu_changed.iter().take(BASELINE_DIFF_ROWS).map(|(pid, name, old, new)| format!("  {:<7} {:<24} {:>+6.1}% ({:.1}% → {:.1}%)", pid.as_u32(), name, new.0 - old.0, old.0, new.0)).collect(),
+    );
+    lines
+}
+
+// Helper for centering the modal
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+// Border style for a panel, emphasised when it holds keyboard focus
+fn panel_block<'a>(title: String, theme: &Theme, focused: bool) -> Block<'a> {
+    let block = Block::default().title(title).borders(Borders::ALL);
+    if focused {
+        block
+            .border_type(BorderType::Thick)
+            .border_style(Style::default().fg(theme.highlight_bg).add_modifier(Modifier::BOLD))
+    } else {
+        block.border_style(Style::default().fg(theme.border))
+    }
+}
+
+fn disk_used_percent(disk: &DiskInfo) -> u64 {
+    if disk.total == 0 {
+        return 0;
+    }
+    (disk.total.saturating_sub(disk.available) as f64 / disk.total as f64 * 100.0) as u64
+}
+
+// Header hints with each rebindable action labelled by its current key
+fn header_hints(keymap: &HashMap<KeyCode, Action>) -> Vec<(String, u8)> {
+    HEADER_HINTS
+        .iter()
+        .filter_map(|(key, label, priority)| {
+            let key = match key {
+                HintKey::Fixed(key) => key.to_string(),
+                // Prefer single-character keys, which are what the defaults advertise
+                HintKey::Bound(action) => keymap
+                    .iter()
+                    .filter(|(_, bound)| *bound == action)
+                    .map(|(&code, _)| key_label(code))
+                    .min_by_key(|label| (label.chars().count(), label.clone()))?,
+            };
+            Some((format!("[{}] {}", key, label), *priority))
+        })
+        .collect()
+}
+
+// Short name for a key as shown in the header
+fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_uppercase().to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
+
+// Parses a config key spec: a single character or a key name such as "esc", "down" or "f5"
+fn parse_key(spec: &str) -> Option<KeyCode> {
+    let mut chars = spec.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match spec.to_lowercase().as_str() {
+        "esc" | "escape" => KeyCode::Esc,
+        "enter" | "return" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        name => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+    };
+    Some(code)
+}
+
+// Drops the least important hints until the rest fit in `available` columns
+fn fit_header_hints(hints: &[(String, u8)], available: usize) -> String {
+    let mut kept: Vec<bool> = vec![true; hints.len()];
+    let width = |kept: &[bool]| -> usize {
+        hints.iter().zip(kept).filter(|(_, &k)| k).map(|((hint, _), _)| hint.chars().count() + 1).sum::<usize>() + 1
+    };
+    let mut by_priority: Vec<usize> = (0..hints.len()).collect();
+    by_priority.sort_by_key(|&i| std::cmp::Reverse(hints[i].1));
+    for i in by_priority {
+        if width(&kept) <= available {
+            break;
+        }
+        kept[i] = false;
+    }
+    if !kept.iter().any(|&k| k) {
+        return String::new();
+    }
+    let hints: Vec<&str> = hints.iter().zip(&kept).filter(|(_, &k)| k).map(|((hint, _), _)| hint.as_str()).collect();
+    format!(" {} ", hints.join(" "))
+}
+
+// CPU time as H:MM:SS, or M:SS.ss under an hour
+fn format_cpu_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:05.2}", secs / 60, time.as_secs_f64() % 60.0)
+    }
+}
+
+fn memory_percent(memory: u64, total: u64) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    (memory as f64 / total as f64 * 100.0) as f32
+}
+
+// The filter bar's query. `path:` matches the executable path, so same-named binaries
+// from different installs can be told apart; `file:` and `port:` find the processes
+// holding a file or socket open; anything else matches the process name. A `file:`
+// or `port:` query without a usable target yet filters nothing.
+enum ProcessFilter {
+    Name(String, bool), // Text and whether case must match
+    Path(String, bool),
+    Pids(HashSet<Pid>), // file: and port: walk every process's fds, so resolve them once up front
+}
+
+impl ProcessFilter {
+    fn parse(query: &str, case_sensitive: bool) -> Option<Self> {
+        let fold = |text: &str| if case_sensitive { text.to_string() } else { text.to_lowercase() };
+        let filter = if let Some(path) = query.strip_prefix("path:") {
+            ProcessFilter::Path(fold(path), case_sensitive)
+        } else if let Some(file) = query.strip_prefix("file:") {
+            let file = file.trim();
+            if file.is_empty() {
+                return None;
+            }
+            ProcessFilter::Pids(pids_with_fd(|target| target.contains(file)))
+        } else if let Some(port) = query.strip_prefix("port:") {
+            let inodes = socket_inodes_on_port(port.trim().parse().ok()?);
+            ProcessFilter::Pids(pids_with_fd(|target| {
+                target.strip_prefix("socket:[").and_then(|t| t.strip_suffix(']')).and_then(|inode| inode.parse().ok()).is_some_and(|inode| inodes.contains(&inode))
+            }))
+        } else {
+            ProcessFilter::Name(fold(query), case_sensitive)
+        };
+        Some(filter)
+    }
+
+    fn matches(&self, process: &Process) -> bool {
+        let contains = |text: &str, needle: &str, case_sensitive: bool| if case_sensitive { text.contains(needle) } else { text.to_lowercase().contains(needle) };
+        match self {
+            ProcessFilter::Name(name, case_sensitive) => contains(process.name(), name, *case_sensitive),
+            ProcessFilter::Path(path, case_sensitive) => process.exe().is_some_and(|exe| contains(&exe.to_string_lossy(), path, *case_sensitive)),
+            ProcessFilter::Pids(pids) => pids.contains(&process.pid()),
+        }
+    }
+}
+
+// Splits a Prometheus text-format sample, `name{label="value",...} value [timestamp]`
+fn parse_sample(line: &str) -> Option<(&str, HashMap<String, String>, f64)> {
+    let name_end = line.find(|c: char| c == '{' || c.is_whitespace())?;
+    let (name, mut rest) = line.split_at(name_end);
+    let mut labels = HashMap::new();
+    if let Some(mut inner) = rest.strip_prefix('{') {
+        loop {
+            inner = inner.trim_start_matches([',', ' ']);
+            if let Some(after) = inner.strip_prefix('}') {
+                rest = after;
+                break;
+            }
+            let (key, after) = inner.split_once("=\"")?;
+            let mut value = String::new();
+            let mut chars = after.char_indices();
+            let end = loop {
+                let (i, c) = chars.next()?;
+                match c {
+                    '\\' => match chars.next()?.1 {
+                        'n' => value.push('\n'),
+                        escaped => value.push(escaped),
+                    },
+                    '"' => break i,
+                    c => value.push(c),
+                }
+            };
+            labels.insert(key.trim().to_string(), value);
+            inner = &after[end + 1..];
+        }
+    }
+    let value = rest.split_whitespace().next()?.parse().ok()?;
+    Some((name, labels, value))
+}
+
+// Least-squares slope of evenly spaced samples, in units per sample; positive when rising
+fn slope(samples: &VecDeque<f32>) -> f32 {
+    let n = samples.len() as f32;
+    if n < 2.0 {
+        return 0.0;
+    }
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = samples.iter().sum::<f32>() / n;
+    let (covariance, variance) = samples.iter().enumerate().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        let dx = x as f32 - mean_x;
+        (cov + dx * (y - mean_y), var + dx * dx)
+    });
+    covariance / variance
+}
+
+// Mean over the last HISTORY_LEN ticks, the window the per-tick sparklines show
+fn history_average(history: &VecDeque<u64>) -> u64 {
+    let recent = history.len().min(HISTORY_LEN);
+    if recent == 0 {
+        return 0;
+    }
+    history.iter().rev().take(recent).sum::<u64>() / recent as u64
+}
+
+// Usage of whichever core was busiest at each tick
+fn max_core_history(core_history: &[VecDeque<u64>]) -> VecDeque<u64> {
+    let len = core_history.iter().map(VecDeque::len).min().unwrap_or(0);
+    (0..len).map(|i| core_history.iter().map(|history| history[i]).max().unwrap_or(0)).collect()
+}
+
+// Highest value over the last HISTORY_LEN ticks
+fn history_peak(history: &VecDeque<u64>) -> u64 {
+    history.iter().rev().take(HISTORY_LEN).copied().max().unwrap_or(0)
+}
+
+// Peak-hold tick drawn over a gauge, as on a VU meter, so a brief spike stays
+// visible after usage drops. The label row is left alone.
+fn render_peak_marker(f: &mut ratatui::Frame, gauge_area: Rect, percent: u64, style: Style) {
+    let inner = Block::default().borders(Borders::ALL).inner(gauge_area);
+    if inner.width == 0 || percent == 0 {
+        return;
+    }
+    let x = inner.x + ((percent.min(100) as u32 * inner.width as u32 / 100) as u16).min(inner.width - 1);
+    let label_row = inner.y + inner.height / 2;
+    for y in inner.top()..inner.bottom() {
+        if y != label_row {
+            f.buffer_mut()[(x, y)].set_symbol("▏").set_style(style);
+        }
+    }
+}
+
+// Sparkline buffers start at HISTORY_LEN zeros and grow up to `cap` raw samples
+fn push_sample(history: &mut VecDeque<u64>, value: u64, cap: usize) {
+    if history.len() >= cap {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+// One sparkline column at the current granularity
+struct Bucket {
+    min: u64,
+    avg: u64,
+    max: u64,
+}
+
+// The newest HISTORY_LEN buckets of `samples` raw values each, oldest first.
+// Buckets are aligned to the newest sample so the last column is always complete.
+fn bucket_history(history: &VecDeque<u64>, samples: usize) -> Vec<Bucket> {
+    let values: Vec<u64> = history.iter().rev().take(HISTORY_LEN * samples).copied().collect();
+    let mut buckets: Vec<Bucket> = values.chunks(samples).map(|chunk| Bucket {
+        min: *chunk.iter().min().unwrap_or(&0),
+        avg: chunk.iter().sum::<u64>() / chunk.len() as u64,
+        max: *chunk.iter().max().unwrap_or(&0),
+    }).collect();
+    buckets.reverse();
+    buckets
+}
+
+// The history bucketed to the current granularity and cut to fit `width`, padded
+// with zeroes on the left so it ends at the right edge, with its block titled
+// and carrying a min/avg/max/now readout
+fn history_window<'a>(app: &App, theme: &Theme, history: &VecDeque<u64>, title: &str, width: usize) -> (Vec<u64>, Block<'a>) {
+    let mut buckets = bucket_history(history, app.granularity.samples_per_bucket(app.tick_rate));
+    buckets.drain(..buckets.len().saturating_sub(width));
+
+    let title = if app.granularity == Granularity::Tick {
+        format!(" {} ", title)
+    } else {
+        let low = buckets.iter().map(|b| b.min).min().unwrap_or(0);
+        let high = buckets.iter().map(|b| b.max).max().unwrap_or(0);
+        format!(" {} · {} avg, {}–{} ", title, app.granularity.label(), app.format_count(low), app.format_count(high))
+    };
+    let values: Vec<u64> = buckets.iter().map(|b| b.avg).collect();
+    let readout = format!(
+        " min {} / avg {} / max {} / now {} ",
+        app.format_count(values.iter().min().copied().unwrap_or(0)),
+        app.format_count(values.iter().sum::<u64>() / values.len().max(1) as u64),
+        app.format_count(values.iter().max().copied().unwrap_or(0)),
+        app.format_count(history.back().copied().unwrap_or(0)),
+    );
+
+    let data: Vec<u64> = std::iter::repeat_n(0, width.saturating_sub(values.len())).chain(values).collect();
+    let block = Block::default().title(title).title_bottom(readout).borders(Borders::ALL).border_style(Style::default().fg(theme.border));
+    (data, block)
+}
+
+// A history sparkline with the newest sample at the right edge. The bottom border
+// reads min / avg / max / now for the visible window; bucketed views add the raw
+// range the averages hide to the title, e.g. " CPU · 30s avg, 4–97 ".
+fn render_history_sparkline(f: &mut ratatui::Frame, app: &App, theme: &Theme, history: &VecDeque<u64>, title: &str, color: Color, area: Rect) {
+    let (data, block) = history_window(app, theme, history, title, area.width.saturating_sub(2) as usize);
+    f.render_widget(Sparkline::default().block(block).data(&data).style(Style::default().fg(color)), area);
+}
+
+// A percentage history as a line chart over rules at 25/50/75%, so values can be
+// read off the graph
+fn render_gridded_history(f: &mut ratatui::Frame, app: &App, theme: &Theme, history: &VecDeque<u64>, title: &str, color: Color, area: Rect) {
+    let width = area.width.saturating_sub(2) as usize;
+    let (data, block) = history_window(app, theme, history, title, width);
+    let right = width.saturating_sub(1) as f64;
+    let rules: Vec<[(f64, f64); 2]> = [25.0, 50.0, 75.0].map(|y| [(0.0, y), (right, y)]).to_vec();
+    let points: Vec<(f64, f64)> = data.iter().enumerate().map(|(x, &v)| (x as f64, v.min(100) as f64)).collect();
+    // Rules first so the data line draws over them
+    let mut datasets: Vec<Dataset> = rules.iter().map(|rule| {
+        Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(Color::DarkGray)).data(rule)
+    }).collect();
+    datasets.push(Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(color)).data(&points));
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(Axis::default().bounds([0.0, right]))
+        .y_axis(Axis::default().bounds([0.0, 100.0]).labels(["0", "50", "100"]).style(Style::default().fg(theme.border)));
+    f.render_widget(chart, area);
+}
+
+// A percentage history placed by when each sample was taken, over as many seconds
+// as the plain graph has ticks. Samples are dots, so a stall or a suspend shows
+// as a gap rather than being squeezed out.
+fn render_timed_history(f: &mut ratatui::Frame, app: &App, theme: &Theme, history: &VecDeque<u64>, title: &str, color: Color, area: Rect) {
+    let span = app.tick_rate.as_secs_f64() * area.width.saturating_sub(2).max(1) as f64;
+    let now = SystemTime::now();
+    // Histories start padded with zeroes that have no time, so pair from the newest
+    let points: Vec<(f64, f64)> = history.iter().rev().zip(app.sample_times.iter().rev())
+        .map(|(&value, &at)| (span - now.duration_since(at).unwrap_or_default().as_secs_f64(), value.min(100) as f64))
+        .take_while(|&(x, _)| x >= 0.0)
+        .collect();
+    let values: Vec<u64> = points.iter().map(|&(_, y)| y as u64).collect();
+    let readout = format!(
+        " min {} / avg {} / max {} / now {} ",
+        values.iter().min().copied().unwrap_or(0),
+        values.iter().sum::<u64>() / values.len().max(1) as u64,
+        values.iter().max().copied().unwrap_or(0),
+        history.back().copied().unwrap_or(0),
+    );
+    let block = Block::default()
+        .title(format!(" {} · last {:.0}s ", title, span))
+        .title_bottom(readout)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border));
+    let dataset = Dataset::default().marker(symbols::Marker::Braille).graph_type(GraphType::Scatter).style(Style::default().fg(color)).data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(block)
+        .x_axis(Axis::default().bounds([0.0, span]))
+        .y_axis(Axis::default().bounds([0.0, 100.0]).labels(["0", "50", "100"]).style(Style::default().fg(theme.border)));
+    f.render_widget(chart, area);
+}
+
+// Explains an empty panel instead of leaving a blank bordered box
+fn render_placeholder(f: &mut ratatui::Frame, area: Rect, message: &str) {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    if inner.height == 0 {
+        return;
+    }
+    let line_area = Rect { y: inner.y + inner.height / 2, height: 1, ..inner };
+    let text = Paragraph::new(message)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC));
+    f.render_widget(text, line_area);
+}
+
+// Core number of a per-core sensor label such as "coretemp Core 3"
+fn core_sensor_index(label: &str) -> Option<usize> {
+    label.rsplit_once("Core ")?.1.trim().parse().ok()
+}
+
+// Temperature of a logical CPU's physical core. Sensors number physical cores, so
+// hyperthread siblings share a reading. Without per-core sensors this is the
+// hottest sensor, usually the package.
+fn core_temperature(app: &App, cpu: usize) -> Option<f32> {
+    let core = app.core_topology.as_ref().map_or(cpu, |topology| topology[cpu]);
+    app.core_temps.get(&core).copied().or(app.temperature)
+}
+
+// One row per logical CPU with a usage bar
+fn render_core_list(f: &mut ratatui::Frame, app: &mut App, theme: &Theme, block: Block, row_highlight: Style, area: Rect) {
+    let temp_width = if app.show_core_temps { 6 } else { 0 };
+    let bar_width = area.width.saturating_sub(2 + 6 + 4 + 6 + 3 + temp_width) as usize; // borders, name, core, percent, temp, spacing
+    // Hyperthread siblings are listed next to each other, tagged with their physical core
+    let mut cpu_order: Vec<usize> = (0..app.core_history.len()).collect();
+    let core_usage = |i: usize| *app.core_history[i].back().unwrap_or(&0);
+    // Condensed, the busiest cores come first and the idle tail is one summary row
+    let mut rest = Vec::new();
+    if app.busiest_cores {
+        cpu_order.sort_by_key(|&i| (std::cmp::Reverse(core_usage(i)), i));
+        rest = cpu_order.split_off(cpu_order.len().min(BUSIEST_CORES));
+    } else if let Some(topology) = &app.core_topology {
+        cpu_order.sort_by_key(|&i| (topology[i], i));
+    }
+    let mut core_rows: Vec<Row> = cpu_order.iter().map(|&i| {
+        let usage = *app.core_history[i].back().unwrap_or(&0) as f32;
+        let physical = app.core_topology.as_ref().map(|t| format!("c{}", t[i])).unwrap_or_default();
+        Row::new(vec![
+            format!("CPU{}", i),
+            physical,
+            usage_bar(usage, bar_width),
+            format!("{:.0}%", usage),
+            core_temperature(app, i).filter(|_| app.show_core_temps).map(|celsius| format!("{:.0}°C", celsius)).unwrap_or_default(),
+        ])
+        .style(Style::default().fg(if usage > 80.0 { theme.gauge_cpu_high } else { theme.gauge_cpu_low }))
+    }).collect();
+    // "+N avg": the remaining cores' average usage
+    if !rest.is_empty() {
+        let average = rest.iter().map(|&i| core_usage(i)).sum::<u64>() as f32 / rest.len() as f32;
+        core_rows.push(Row::new(vec![
+            format!("+{}", rest.len()),
+            "avg".to_string(),
+            usage_bar(average, bar_width),
+            format!("{:.0}%", average),
+        ]).style(Style::default().fg(theme.border)));
+    }
+    let core_table = Table::new(core_rows, [Constraint::Length(6), Constraint::Length(4), Constraint::Min(0), Constraint::Length(6), Constraint::Length(temp_width)])
+        .block(block)
+        .row_highlight_style(row_highlight);
+    f.render_stateful_widget(core_table, area, &mut app.core_state);
+}
+
+// Green at idle through yellow to red at full load
+fn heat_color(percent: f32) -> Color {
+    let p = percent.clamp(0.0, 100.0) / 100.0;
+    let red = (p * 2.0).min(1.0) * 255.0;
+    let green = ((1.0 - p) * 2.0).min(1.0) * 255.0;
+    Color::Rgb(red as u8, green as u8, 0)
+}
+
+// One coloured cell per core, wrapped to the panel width; scales to 64+ cores
+fn render_core_heatmap(f: &mut ratatui::Frame, app: &App, block: Block, area: Rect) {
+    const CELL_WIDTH: usize = 4;
+    let inner = block.inner(area);
+    let per_row = (inner.width as usize / CELL_WIDTH).max(1);
+    let lines: Vec<Line> = app.core_history.chunks(per_row).enumerate().map(|(row, chunk)| {
+        Line::from(chunk.iter().enumerate().map(|(col, history)| {
+            Span::styled(
+                format!("{:>3} ", row * per_row + col),
+                Style::default().fg(Color::Black).bg(heat_color(*history.back().unwrap_or(&0) as f32)),
+            )
+        }).collect::<Vec<_>>())
+    }).collect();
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+// Text bar for per-core usage, e.g. "||||||    "
+fn usage_bar(percent: f32, width: usize) -> String {
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0) * width as f32).round() as usize;
+    format!("{}{}", "|".repeat(filled), " ".repeat(width.saturating_sub(filled)))
+}
+
+// Per-core usage stacked so each line is the running total up to that core; the
+// top line equals the aggregate, and the gaps show each core's share
+fn render_stacked_cpu_chart(f: &mut ratatui::Frame, app: &App, theme: &Theme, area: Rect) {
+    const CORE_COLORS: [Color; 6] = [Color::Green, Color::Cyan, Color::Magenta, Color::Yellow, Color::Blue, Color::Red];
+
+    let core_count = app.core_history.len().max(1) as f64;
+    let mut running = vec![0.0; HISTORY_LEN];
+    let series: Vec<Vec<(f64, f64)>> = app.core_history.iter().map(|history| {
+        history.iter().enumerate().map(|(x, &usage)| {
+            running[x] += usage as f64 / core_count;
+            (x as f64, running[x])
+        }).collect()
+    }).collect();
+
+    let datasets: Vec<Dataset> = series.iter().enumerate().map(|(i, points)| {
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(CORE_COLORS[i % CORE_COLORS.len()]))
+            .data(points)
+    }).collect();
+
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(" CPU (stacked per core) ").borders(Borders::ALL).border_style(Style::default().fg(theme.border)))
+        .x_axis(Axis::default().bounds([0.0, (HISTORY_LEN - 1) as f64]))
+        .y_axis(Axis::default().bounds([0.0, 100.0]));
+    f.render_widget(chart, area);
+}
+
+// Field-per-line summary of a process, shared by the details popup and sidebar
+fn process_details<'a>(app: &App, theme: &Theme, process: &'a Process) -> Vec<Line<'a>> {
+    let pid = process.pid();
+    let (exe, cmd) = if app.anonymize {
+        ("(hidden)".to_string(), String::new())
+    } else {
+        (process.exe().map(|p| p.display().to_string()).unwrap_or_else(|| "n/a".to_string()), process.cmd().join(" "))
+    };
+    let affinity = match app.input_mode {
+        InputMode::Affinity => format!("{}_ (Enter to apply, Esc to cancel)", app.affinity_input),
+        InputMode::Details => get_affinity(pid).map(|cores| format!("{} (a to edit)", format_core_list(&cores))).unwrap_or_else(|| "n/a".to_string()),
+        _ => get_affinity(pid).map(|cores| format_core_list(&cores)).unwrap_or_else(|| "n/a".to_string()),
+    };
+    // Unwrapped, the popup shows the command from the scroll offset so long
+    // structured command lines keep their shape
+    let scrolled = app.input_mode == InputMode::Details && !app.details_wrap;
+    let (cmd, cmd_hint) = if scrolled {
+        (cmd.chars().skip(app.details_scroll).collect(), format!("(←/→ to scroll, w to wrap, c to copy; from column {})", app.details_scroll + 1))
+    } else if app.input_mode == InputMode::Details {
+        (cmd, "(w to scroll instead of wrapping, c to copy)".to_string())
+    } else {
+        (cmd, String::new())
+    };
+    // Time mostly in the kernel points at syscalls rather than userland computation
+    let cpu_split = match process_cpu_times(pid) {
+        Some((user, system)) => {
+            let total = (user + system).as_secs_f64();
+            let user_share = if total > 0.0 { user.as_secs_f64() / total * 100.0 } else { 0.0 };
+            format!("{} user / {} system ({:.0}% user)", format_cpu_time(user), format_cpu_time(system), user_share)
+        }
+        None => "n/a".to_string(),
+    };
+    vec![
+        Line::from(vec![Span::styled("PID: ", Style::default().fg(theme.border)), Span::styled(app.format_count(pid.as_u32() as u64), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Name: ", Style::default().fg(theme.border)), Span::styled(app.process_label(pid, process.name()), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Status: ", Style::default().fg(theme.border)), Span::styled(format!("{:?}", process.status()), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("CPU Usage: ", Style::default().fg(theme.border)), Span::styled(format!("{:.2}%", process.cpu_usage()), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("CPU Time: ", Style::default().fg(theme.border)), Span::styled(cpu_split, Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Memory: ", Style::default().fg(theme.border)), Span::styled(format!("{:.1} MB", process.memory() as f64 / 1_048_576.0), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Threads: ", Style::default().fg(theme.border)), Span::styled(process.tasks().map_or_else(|| "n/a".to_string(), |tasks| tasks.len().to_string()), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Virtual Mem: ", Style::default().fg(theme.border)), Span::styled(format!("{:.1} MB", process.virtual_memory() as f64 / 1_048_576.0), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Swap: ", Style::default().fg(theme.border)), Span::styled(process_swap(pid).map_or_else(|| "n/a".to_string(), |swap| format!("{:.1} MB", swap as f64 / 1_048_576.0)), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Start Time: ", Style::default().fg(theme.border)), Span::styled(format!("{}s ago", app.format_count(process.run_time())), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Started: ", Style::default().fg(theme.border)), Span::styled(format_timestamp(process.start_time()), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Disk Read: ", Style::default().fg(theme.border)), Span::styled(format!("{:.1} KB", process.disk_usage().read_bytes as f64 / 1024.0), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Disk Write: ", Style::default().fg(theme.border)), Span::styled(format!("{:.1} KB", process.disk_usage().written_bytes as f64 / 1024.0), Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("CPU Affinity: ", Style::default().fg(theme.border)), Span::styled(affinity, Style::default().fg(theme.text))]),
+        Line::from(vec![Span::styled("Executable: ", Style::default().fg(theme.border)), Span::styled(exe, Style::default().fg(theme.text))]),
+        Line::from(""),
+        Line::from(vec![Span::styled("Command: ", Style::default().fg(theme.border)), Span::styled(cmd_hint, Style::default().fg(Color::DarkGray))]),
+        Line::from(Span::styled(cmd, Style::default().fg(theme.text))),
+    ]
+}
+
+// User, system, iowait and steal stacked in that order, so the top line is total busy
+// time and a thick iowait or steal band stands out
+fn render_cpu_breakdown_chart(f: &mut ratatui::Frame, app: &App, theme: &Theme, area: Rect) {
+    const CATEGORIES: [(&str, Color); 4] = [("user", Color::Green), ("system", Color::Red), ("iowait", Color::Yellow), ("steal", Color::Magenta)];
+
+    let mut running = vec![0.0; app.cpu_breakdown_history.len()];
+    let series: Vec<Vec<(f64, f64)>> = (0..CATEGORIES.len()).map(|i| {
+        app.cpu_breakdown_history.iter().enumerate().map(|(x, breakdown)| {
+            running[x] += breakdown.values()[i];
+            (x as f64, running[x])
+        }).collect()
+    }).collect();
+
+    let datasets: Vec<Dataset> = series.iter().zip(CATEGORIES).map(|(points, (name, color))| {
+        Dataset::default()
+            .name(name)
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(color))
+            .data(points)
+    }).collect();
+
+    let latest = app.cpu_breakdown_history.back().copied().unwrap_or_default();
+    let title = format!(" CPU usr {:.0}% sys {:.0}% iowait {:.0}% steal {:.0}% ", latest.user, latest.system, latest.iowait, latest.steal);
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(title).borders(Borders::ALL).border_style(Style::default().fg(theme.border)))
+        .x_axis(Axis::default().bounds([0.0, (HISTORY_LEN - 1) as f64]))
+        .y_axis(Axis::default().bounds([0.0, 100.0]));
+    f.render_widget(chart, area);
+}
+
+// Process table with its filter bar and optional details sidebar
+fn render_process_panel(f: &mut ratatui::Frame, app: &mut App, theme: &Theme, area: Rect) {
+    // The filter bar can be hidden, but never while a query is active or being typed
+    let filter_visible = app.show_filter_bar || !app.search_query.is_empty() || matches!(app.input_mode, InputMode::Editing | InputMode::PidEntry | InputMode::TickRateEntry | InputMode::ProfileName);
+    // The details sidebar takes the right of the process area and follows the selection
+    let sidebar_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Percentage(if app.details_sidebar { 40 } else { 0 })])
+        .split(area);
+    let process_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(if filter_visible { 3 } else { 0 })]) // Table + Search Bar
+        .split(sidebar_chunks[0]);
+
+    let mut widths = vec![
+        Constraint::Length(6), // PID
+        Constraint::Percentage(40),
+        Constraint::Percentage(20),
+        Constraint::Percentage(20),
+        Constraint::Length(7), // MEM%
+    ];
+    // MEM% orders the same as MEM, so both carry the arrow
+    let mut header_cells = match (app.sort_by, app.show_cpu_time) {
+        (SortBy::Cpu, false) => vec!["PID", app.name_display.header(), "CPU ▼", "MEM", "MEM%"],
+        (SortBy::Cpu, true) => vec!["PID", app.name_display.header(), "CPU TIME ▼", "MEM", "MEM%"],
+        (SortBy::Memory, false) => vec!["PID", app.name_display.header(), "CPU", "MEM ▼", "MEM% ▼"],
+        (SortBy::Memory, true) => vec!["PID", app.name_display.header(), "CPU TIME", "MEM ▼", "MEM% ▼"],
+        (SortBy::Trend, false) => vec!["PID", app.name_display.header(), "CPU TREND ▼", "MEM", "MEM%"],
+        (SortBy::Trend, true) => vec!["PID", app.name_display.header(), "CPU TIME", "MEM", "MEM%"],
+        (SortBy::Connections, false) => vec!["PID", app.name_display.header(), "CPU", "MEM", "MEM%"],
+        (SortBy::Connections, true) => vec!["PID", app.name_display.header(), "CPU TIME", "MEM", "MEM%"],
+    };
+    let show_connections = app.sort_by == SortBy::Connections;
+    if show_connections {
+        widths.push(Constraint::Length(7));
+        header_cells.push("CONN ▼");
+    }
+    if app.show_containers {
+        widths.push(Constraint::Length(16));
+        header_cells.push("Container");
+    }
+    if app.show_swap_column {
+        widths.push(Constraint::Length(10));
+        header_cells.push("SWAP");
+    }
+    if app.show_start_column {
+        widths.push(Constraint::Length(16));
+        header_cells.push("Started");
+    }
+    if app.show_process_net {
+        widths.extend([Constraint::Length(10), Constraint::Length(10)]);
+        header_cells.extend(["NS RX/s", "NS TX/s"]);
+    }
+
+    // Our own row is tagged so the monitor's overhead is easy to spot
+    let own_pid = Pid::from_u32(std::process::id());
+    let name_width = Layout::horizontal(widths.clone()).spacing(app.density.column_spacing()).split(Block::default().borders(Borders::ALL).inner(process_chunks[0]))[1].width as usize;
+    let mut rows: Vec<Row> = app.processes.iter().map(|info| {
+        let mut name = app.display_name(info, name_width);
+        if info.pid == own_pid {
+            name.push_str(" (self)");
+        }
+        // Stopped and zombie processes stand out without opening their details
+        let abnormal = match info.status {
+            ProcessStatus::Zombie if app.mark_abnormal => Some((" <defunct>", Modifier::DIM | Modifier::CROSSED_OUT)),
+            ProcessStatus::Stop if app.mark_abnormal => Some((" (stopped)", Modifier::DIM)),
+            _ => None,
+        };
+        if let Some((tag, _)) = abnormal {
+            name.push_str(tag);
+        }
+        let mut cells = vec![
+            format!("{}", info.pid),
+            name,
+            if app.show_cpu_time {
+                info.cpu_time.map_or_else(|| "-".to_string(), format_cpu_time)
+            } else if app.sort_by == SortBy::Trend {
+                format!("{:.1}% {:+.1}", info.cpu, info.cpu_trend)
+            } else {
+                format!("{:.1}%", info.cpu)
+            },
+            format!("{:.1} MB", info.mem as f64 / 1_048_576.0),
+            format!("{:.1}%", info.mem_percent),
+        ];
+        if show_connections {
+            cells.push(app.connection_counts.get(&info.pid).map_or_else(|| "-".to_string(), usize::to_string));
+        }
+        if app.show_containers {
+            cells.push(app.containers.get(&info.pid).cloned().flatten().unwrap_or_else(|| "-".to_string()));
+        }
+        if app.show_swap_column {
+            cells.push(app.process_swap.get(&info.pid).map_or_else(|| "-".to_string(), |&swap| format!("{:.1} MB", swap as f64 / 1_048_576.0)));
+        }
+        if app.show_start_column {
+            cells.push(format_timestamp(info.start_time));
+        }
+        if app.show_process_net {
+            match app.process_net.get(&info.pid) {
+                Some((rx, tx)) => {
+                    cells.push(format!("{:.1} KB/s", *rx as f64 / 1024.0));
+                    cells.push(format!("{:.1} KB/s", *tx as f64 / 1024.0));
+                }
+                None => cells.extend(["-".to_string(), "-".to_string()]),
+            }
+        }
+        let fg = if app.vanished_pids.contains(&info.pid) {
+            Color::Red
+        } else if app.new_pids.contains(&info.pid) {
+            Color::Green
+        } else if app.watched(&info.name) {
+            theme.graph_net_rx
         } else {
-            procs.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap_or(std::cmp::Ordering::Equal));
-            procs.truncate(50); // Increased list size
+            theme.text
+        };
+        let mut style = Style::default().fg(fg);
+        if app.watched(&info.name) {
+            style = style.add_modifier(Modifier::BOLD);
         }
-        
-        self.processes = procs.iter().map(|p| (
-            p.pid(), 
-            p.name().to_string(), 
-            p.cpu_usage(), 
-            p.memory()
-        )).collect();
+        if app.killed_pids.contains(&info.pid) {
+            style = style.fg(Color::Red).add_modifier(Modifier::CROSSED_OUT);
+        }
+        if app.compare_mark == Some(info.pid) {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        if let Some((_, modifier)) = abnormal {
+            style = style.add_modifier(modifier);
+        }
+        Row::new(cells)
+        .bottom_margin(app.density.row_margin())
+        .style(if info.pid == own_pid { style.add_modifier(Modifier::ITALIC) } else { style })
+    }).collect();
+    let column_count = 5 + show_connections as usize + app.show_containers as usize + app.show_swap_column as usize + app.show_start_column as usize + 2 * app.show_process_net as usize;
+    let expanded_at = app.expanded_pid.and_then(|pid| app.processes.iter().position(|info| info.pid == pid));
+    if let Some(at) = expanded_at {
+        let thread_rows = app.threads.iter().map(|thread| {
+            let mut cells = vec![
+                thread.tid.to_string(),
+                format!(" └ {}", if app.anonymize { "thread" } else { &thread.name }),
+                if app.show_cpu_time { format_cpu_time(thread.cpu_time) } else { format!("{:.1}%", thread.cpu) },
+            ];
+            cells.resize(column_count, String::new());
+            Row::new(cells).bottom_margin(app.density.row_margin()).style(Style::default().fg(theme.border))
+        });
+        rows.splice(at + 1..at + 1, thread_rows);
+    }
+    // Past the end of `processes`, so the selection never lands on it
+    if let Some((count, cpu, mem)) = app.unlisted {
+        let mut cells = vec![
+            String::new(),
+            format!("(other {} processes)", count),
+            format!("{:.1}%", cpu),
+            format!("{:.1} MB", mem as f64 / 1_048_576.0),
+            format!("{:.1}%", memory_percent(mem, app.system.total_memory())),
+        ];
+        cells.resize(column_count, String::new());
+        rows.push(Row::new(cells).style(Style::default().fg(theme.border).add_modifier(Modifier::ITALIC)));
     }
 
-    fn next_process(&mut self) {
-        if self.processes.is_empty() { return; }
-        let i = match self.process_state.selected() {
-            Some(i) => if i >= self.processes.len() - 1 { 0 } else { i + 1 },
-            None => 0,
-        };
-        self.process_state.select(Some(i));
+    let mut table_title = if app.search_query.is_empty() {
+        let limit = PROCESS_LIMITS[app.process_limit].map_or("All".to_string(), |n| format!("Top {}", n));
+        match app.sort_by {
+            SortBy::Cpu => format!(" {} Processes (Enter to Inspect) ", limit),
+            SortBy::Memory => format!(" {} Processes by Memory (Enter to Inspect) ", limit),
+            SortBy::Trend => format!(" {} Processes by Rising CPU (Enter to Inspect) ", limit),
+            SortBy::Connections => format!(" {} Processes by Connections (Enter to Inspect) ", limit),
+        }
+    } else {
+        format!(" Search: '{}' ", app.search_query)
+    };
+    if let Some((unlisted, _, _)) = app.unlisted {
+        let listed = app.processes.iter().filter(|info| !app.vanished_pids.contains(&info.pid)).count();
+        table_title.push_str(&format!("(showing {} of {}) ", listed, listed + unlisted));
+    }
+    if app.freeze_list {
+        table_title.push_str("[FROZEN] ");
+    }
+    if let Some(pid) = app.watch_pid {
+        table_title.push_str(&format!("[WATCHING {}] ", pid));
     }
 
-    fn previous_process(&mut self) {
-        if self.processes.is_empty() { return; }
-        let i = match self.process_state.selected() {
-            Some(i) => if i == 0 { self.processes.len() - 1 } else { i - 1 },
-            None => 0,
-        };
-        self.process_state.select(Some(i));
+    // Totals for the listed processes, to cross-check against the system gauges
+    let live: Vec<&ProcessInfo> = app.processes.iter().filter(|info| !app.vanished_pids.contains(&info.pid)).collect();
+    let (other_count, other_cpu, other_mem) = app.unlisted.unwrap_or_default();
+    let total_cpu: f32 = live.iter().map(|info| info.cpu).sum::<f32>() + other_cpu;
+    let total_mem: u64 = live.iter().map(|info| info.mem).sum::<u64>() + other_mem;
+    let process_totals = format!(
+        " Σ CPU {:.1}% | Σ MEM {:.1} MB | {} processes ",
+        total_cpu,
+        total_mem as f64 / 1_048_576.0,
+        live.len() + other_count
+    );
+
+
+    let mut table = Table::new(rows, widths).column_spacing(app.density.column_spacing());
+    if app.show_table_header {
+        table = table.header(Row::new(header_cells).bottom_margin(app.density.row_margin()).style(Style::default().fg(theme.border)));
     }
+    let table = table
+    .block(panel_block(table_title, theme, app.focused_panel == FocusedPanel::Processes).title_bottom(process_totals))
+    .row_highlight_style(Style::default().bg(theme.highlight_bg).fg(theme.highlight_fg).add_modifier(Modifier::BOLD));
 
-    fn kill_selected_process(&mut self) {
-        if let Some(i) = self.process_state.selected() {
-            if let Some((pid, _, _, _)) = self.processes.get(i) {
-                if let Some(process) = self.system.process(*pid) {
-                    process.kill();
-                }
-            }
+    // Thread rows are display-only, so shift the selection past them while drawing
+    let selected = app.process_state.selected();
+    if let (Some(at), Some(i)) = (expanded_at, selected) {
+        if i > at {
+            app.process_state.select(Some(i + app.threads.len()));
         }
     }
+    f.render_stateful_widget(table, process_chunks[0], &mut app.process_state);
+    app.process_state.select(selected);
+    if app.remote.is_some() {
+        render_placeholder(f, process_chunks[0], "Processes are not available from node-exporter");
+    }
 
-    fn inspect_selected_process(&mut self) {
-        if let Some(i) = self.process_state.selected() {
-            if let Some((pid, _, _, _)) = self.processes.get(i) {
-                self.selected_pid = Some(*pid);
-                self.input_mode = InputMode::Details;
+    if app.details_sidebar {
+        let selected = app.process_state.selected().and_then(|i| app.processes.get(i));
+        let block = Block::default().title(" Details ").borders(Borders::ALL).border_style(Style::default().fg(theme.border));
+        match selected.and_then(|info| app.system.process(info.pid)) {
+            Some(process) => f.render_widget(Paragraph::new(process_details(app, theme, process)).block(block).wrap(Wrap { trim: true }), sidebar_chunks[1]),
+            None => {
+                f.render_widget(block, sidebar_chunks[1]);
+                render_placeholder(f, sidebar_chunks[1], "No process selected");
             }
         }
     }
-}
 
-fn main() -> Result<()> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    let mut app = App::new();
-    let tick_rate = Duration::from_millis(TICK_RATE);
-    let mut last_tick = Instant::now();
+    // Search Input Box
+    let input_style = match app.input_mode {
+        InputMode::Editing | InputMode::PidEntry | InputMode::TickRateEntry | InputMode::ProfileName => Style::default().fg(theme.highlight_bg),
+        _ => Style::default().fg(Color::DarkGray),
+    };
+    
+    let search_text = match app.input_mode {
+        InputMode::Editing => format!("Search ({}, Tab to switch): {}_", if app.search_case_sensitive { "match case" } else { "any case" }, app.search_query),
+        InputMode::PidEntry => format!("Jump to PID: {}_", app.pid_input),
+        InputMode::TickRateEntry => format!("Refresh interval (ms, now {}): {}_", app.tick_rate.as_millis(), app.tick_rate_input),
+        InputMode::ProfileName => format!("Save layout as profile: {}_", app.profile_name_input),
+        _ if app.search_case_sensitive => format!("Search: {} (Press '/', match case)", app.search_query),
+        _ => format!("Search: {} (Press '/')", app.search_query),
+    };
 
-    loop {
-        terminal.draw(|f| ui(f, &mut app))?;
+    if filter_visible {
+        f.render_widget(Paragraph::new(search_text).style(input_style).block(Block::default().borders(Borders::ALL).title(" Filter ").border_style(Style::default().fg(theme.border))), process_chunks[1]);
+    }
+}
 
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
+// Modal popups, drawn over whatever layout is active
+fn render_popups(f: &mut ratatui::Frame, app: &App, theme: &Theme) {
+    // Command Picker Popup (Modal)
+    if app.input_mode == InputMode::Commands {
+        let area = centered_rect(50, 40, f.area());
+        f.render_widget(Clear, area);
+        let target = app.command_target.map(|pid| pid.to_string()).unwrap_or_default();
+        let lines: Vec<Line> = app.command_templates.iter().take(9).enumerate().map(|(i, template)| {
+            Line::from(vec![
+                Span::styled(format!("{}. {} ", i + 1, template.name), Style::default().fg(theme.border)),
+                Span::styled(template.command.replace("{pid}", &target), Style::default().fg(theme.text)),
+            ])
+        }).collect();
+        let block = Block::default()
+            .title(format!(" Run Command on PID {} (Esc to Cancel) ", target))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border).bg(theme.bg))
+            .style(Style::default().bg(theme.bg));
+        f.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: true }), area);
+    }
 
-        if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match app.input_mode {
-                        InputMode::Normal => match key.code {
-                            KeyCode::Char('q') | KeyCode::Esc => app.should_quit = true,
-                            KeyCode::Down | KeyCode::Char('j') => app.next_process(),
-                            KeyCode::Up | KeyCode::Char('k') => app.previous_process(),
-                            KeyCode::Char('x') | KeyCode::Delete => app.kill_selected_process(),
-                            KeyCode::Char('/') => {
-                                app.input_mode = InputMode::Editing;
-                                app.process_state.select(Some(0)); 
-                            }
-                            KeyCode::Enter => app.inspect_selected_process(),
-                            KeyCode::Char('t') => {
-                                app.current_theme = app.current_theme.next();
-                            }
-                            _ => {}
-                        },
-                        InputMode::Editing => match key.code {
-                            KeyCode::Enter | KeyCode::Esc => {
-                                app.input_mode = InputMode::Normal;
-                            }
-                            KeyCode::Backspace => {
-                                app.search_query.pop();
-                            }
-                            KeyCode::Char(c) => {
-                                app.search_query.push(c);
-                            }
-                            _ => {}
-                        },
-                        InputMode::Details => match key.code {
-                            KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace => {
-                                app.input_mode = InputMode::Normal;
-                                app.selected_pid = None;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
+    // Comparison Popup (Modal): each half is the details view
+    if app.input_mode == InputMode::Compare {
+        if let Some((first, second)) = app.compare_pids {
+            let area = centered_rect(80, 60, f.area());
+            f.render_widget(Clear, area);
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(area);
+            for (pid, half) in [first, second].into_iter().zip(halves.iter()) {
+                let block = Block::default()
+                    .title(format!(" Compare PID {} (Esc to Close) ", pid))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border).bg(theme.bg))
+                    .style(Style::default().bg(theme.bg));
+                let lines = match app.system.process(pid) {
+                    Some(process) => process_details(app, theme, process),
+                    None => vec![Line::from(Span::styled("Process exited", Style::default().fg(Color::Red)))],
+                };
+                f.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: true }), *half);
             }
         }
+    }
 
-        if last_tick.elapsed() >= tick_rate {
-            app.on_tick();
-            last_tick = Instant::now();
-        }
-
-        if app.should_quit {
-            break;
+    // Baseline Diff Popup (Modal)
+    if app.input_mode == InputMode::Baseline {
+        if let (Some((taken, baseline)), Some(current)) = (&app.baseline, &app.baseline_current) {
+            let area = centered_rect(70, 70, f.area());
+            f.render_widget(Clear, area);
+            let block = Block::default()
+                .title(" Changes Since Baseline (Esc to Close, b to Rebaseline) ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border).bg(theme.bg))
+                .style(Style::default().bg(theme.bg));
+            let lines = baseline_diff(theme, taken.elapsed(), baseline, current);
+            f.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: false }), area);
         }
     }
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen
-    )?;
-    terminal.show_cursor()?;
+    // 5. Process Details Popup (Modal)
+    if matches!(app.input_mode, InputMode::Details | InputMode::Affinity) {
+        if let Some(pid) = app.selected_pid {
+            if let Some(process) = app.system.process(pid) {
+                let area = centered_rect(60, 50, f.area());
+                f.render_widget(Clear, area); // Clear background
+                
+                let block = Block::default()
+                    .title(" Process Details (Esc to Close) ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border).bg(theme.bg))
+                    .style(Style::default().bg(theme.bg));
+                f.render_widget(block.clone(), area);
 
-    Ok(())
-}
+                // Use inner area for content to avoid overlap with borders
+                let content_area = block.inner(area);
 
-// Helper for centering the modal
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
+                let details_text = process_details(app, theme, process);
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+                let p = Paragraph::new(details_text);
+                let p = if app.details_wrap { p.wrap(Wrap { trim: true }) } else { p };
+
+                f.render_widget(p, content_area);
+            }
+        }
+    }
 }
 
 fn ui(f: &mut ratatui::Frame, app: &mut App) {
-    let theme = app.current_theme.get_theme();
+    let theme = app.theme();
     let area = f.area();
     
     // Set background color for the whole terminal
     let bg_block = Block::default().style(Style::default().bg(theme.bg));
     f.render_widget(bg_block, area);
 
+    // Fullscreen process list hides every other panel, header included
+    if app.process_fullscreen {
+        render_process_panel(f, app, &theme, area);
+        render_popups(f, app, &theme);
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),      // Header
-            Constraint::Percentage(40), // Top: Graphs + Processes
-            Constraint::Percentage(20), // Gauges
+            Constraint::Percentage(if app.show_gauges { 40 } else { 60 }), // Top: Graphs + Processes
+            Constraint::Percentage(if app.show_gauges { 20 } else { 0 }), // Gauges
             Constraint::Percentage(40), // Bottom: Disk + Net
         ])
         .split(area);
 
     // 1. Header
-    let host_name = System::host_name().unwrap_or_else(|| "Unknown".to_string());
-    let header_text = Line::from(vec![
+    let host_name = app.host_name();
+    // A refresh eating half the tick budget is worth flagging
+    let spinner_style = if app.refresh_duration > app.tick_rate / 2 {
+        Style::default().fg(theme.gauge_cpu_high).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.border)
+    };
+    let mut header_spans = vec![
+        Span::styled(format!("{} ", SPINNER_FRAMES[app.spinner_frame]), spinner_style),
         Span::styled(" TERM-DASH v0.5 ", Style::default().fg(theme.bg).bg(theme.border).add_modifier(Modifier::BOLD)),
         Span::styled(format!(" | Host: {} ", host_name), Style::default().fg(theme.text)),
-        Span::styled(" [Q] Quit [/] Filter [Enter] Inspect [X] Kill [T] Theme ", Style::default().fg(theme.text)),
-    ]);
+    ];
+    if app.quiet {
+        header_spans.push(Span::styled(" QUIET ", Style::default().fg(theme.border).add_modifier(Modifier::BOLD)));
+    }
+    if app.zombie_count > 0 {
+        header_spans.push(Span::styled(format!(" Zombies: {} ", app.zombie_count), Style::default().fg(theme.gauge_cpu_high).add_modifier(Modifier::BOLD)));
+    }
+    let status_span = app.status.as_ref().map(|(message, _)| {
+        Span::styled(format!(" {} ", message), Style::default().fg(theme.highlight_fg).bg(theme.highlight_bg))
+    });
+    // Hints get whatever width is left after the fixed parts and any status message
+    let used: usize = header_spans.iter().chain(status_span.iter()).map(|span| span.width()).sum();
+    let available = (chunks[0].width.saturating_sub(2) as usize).saturating_sub(used);
+    header_spans.push(Span::styled(fit_header_hints(&header_hints(&app.keymap), available), Style::default().fg(theme.text)));
+    header_spans.extend(status_span);
+    let header_text = Line::from(header_spans);
     let header = Paragraph::new(header_text)
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.border)));
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.border)).style(Style::default().bg(app.health.color())));
     f.render_widget(header, chunks[0]);
 
     // 2. Top Section
+    // Portrait stacks the panels that normally sit side by side, and lays the graphs
+    // out in a row since the area they share is then wide and short
+    let (across, along) = if app.portrait { (Direction::Vertical, Direction::Horizontal) } else { (Direction::Horizontal, Direction::Vertical) };
     let top_chunks = Layout::default()
-        .direction(Direction::Horizontal)
+        .direction(across)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(chunks[1]);
 
-    // Graphs (Left)
-    let graph_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(top_chunks[0]);
-
-    let cpu_data: Vec<u64> = app.cpu_history.iter().cloned().collect();
-    f.render_widget(Sparkline::default().block(Block::default().title(" CPU ").borders(Borders::ALL).border_style(Style::default().fg(theme.border))).data(&cpu_data).style(Style::default().fg(theme.graph_cpu)), graph_chunks[0]);
-
-    let mem_data: Vec<u64> = app.mem_history.iter().cloned().collect();
-    f.render_widget(Sparkline::default().block(Block::default().title(" Mem ").borders(Borders::ALL).border_style(Style::default().fg(theme.border))).data(&mem_data).style(Style::default().fg(theme.graph_mem)), graph_chunks[1]);
-
-    // Processes List (Right)
-    let process_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(3)]) // Table + Search Bar
-        .split(top_chunks[1]);
-
-    let rows: Vec<Row> = app.processes.iter().map(|(pid, name, cpu, mem)| {
-        Row::new(vec![
-            format!("{}", pid),
-            name.clone(),
-            format!("{:.1}%", cpu),
-            format!("{:.1} MB", *mem as f64 / 1_048_576.0),
+    // Graphs, Process Groups, Memory Breakdown or Listening Ports (Left)
+    if app.show_memory_breakdown {
+        let processes = app.memory_breakdown.len().saturating_sub(3);
+        let bars: Vec<Bar> = app.memory_breakdown.iter().enumerate().map(|(i, (label, bytes))| {
+            let color = if i < processes { theme.graph_mem } else { theme.border };
+            Bar::default()
+                .label(Line::from(label.clone()))
+                .value(bytes / 1_048_576)
+                .text_value(format!("{:.1} MB", *bytes as f64 / 1_048_576.0))
+                .style(Style::default().fg(color))
+                .value_style(Style::default().fg(theme.text).add_modifier(Modifier::BOLD))
+        }).collect();
+        let breakdown_title = format!(" Memory Breakdown (of {:.1} MB) ", app.system.total_memory() as f64 / 1_048_576.0);
+        let breakdown_chart = BarChart::default()
+            .direction(Direction::Horizontal)
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(1)
+            .bar_gap(0)
+            .block(Block::default().title(breakdown_title).borders(Borders::ALL).border_style(Style::default().fg(theme.border)));
+        f.render_widget(breakdown_chart, top_chunks[0]);
+    } else if app.show_groups {
+        let total_memory = app.system.total_memory();
+        let group_rows: Vec<Row> = app.group_usage.iter().map(|group| {
+            Row::new(vec![
+                group.name.clone(),
+                group.processes.to_string(),
+                format!("{:.1}%", group.cpu),
+                format!("{:.1} MB", group.mem as f64 / 1_048_576.0),
+                format!("{:.1}%", memory_percent(group.mem, total_memory)),
+            ])
+            .style(Style::default().fg(if group.processes == 0 { theme.border } else { theme.text }))
+        }).collect();
+        let groups_table = Table::new(group_rows, [
+            Constraint::Min(10),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Length(6),
         ])
-        .style(Style::default().fg(theme.text))
-    }).collect();
-
-    let table_title = if app.search_query.is_empty() {
-        " Top Processes (Enter to Inspect) ".to_string()
+        .header(Row::new(vec!["Group", "Procs", "CPU", "MEM", "MEM%"]).style(Style::default().fg(theme.border)))
+        .block(Block::default().title(" Process Groups ").borders(Borders::ALL).border_style(Style::default().fg(theme.border)));
+        f.render_widget(groups_table, top_chunks[0]);
+        if app.process_groups.is_empty() {
+            render_placeholder(f, top_chunks[0], "No [[groups]] in config.toml");
+        }
+    } else if app.show_ports {
+        let port_rows: Vec<Row> = app.listening_ports.iter().map(|port| {
+            let (pid, name) = match port.pid {
+                Some(pid) => (pid.to_string(), app.system.process(pid).map(|p| app.process_label(pid, p.name())).unwrap_or_default()),
+                None => ("-".to_string(), String::new()),
+            };
+            Row::new(vec![port.proto.to_string(), port.port.to_string(), port.address.clone(), pid, name])
+                .style(Style::default().fg(theme.text))
+        }).collect();
+        let ports_title = if cfg!(target_os = "linux") {
+            format!(" Listening Ports ({}) ", app.listening_ports.len())
+        } else {
+            " Listening Ports (Linux only) ".to_string()
+        };
+        let ports_table = Table::new(port_rows, [
+            Constraint::Length(5),
+            Constraint::Length(6),
+            Constraint::Percentage(40),
+            Constraint::Length(7),
+            Constraint::Min(0),
+        ])
+        .header(Row::new(vec!["Proto", "Port", "Address", "PID", "Process"]).style(Style::default().fg(theme.border)))
+        .block(Block::default().title(ports_title).borders(Borders::ALL).border_style(Style::default().fg(theme.border)));
+        f.render_widget(ports_table, top_chunks[0]);
+        if app.listening_ports.is_empty() {
+            let message = if cfg!(target_os = "linux") { "No listening sockets found" } else { "Port info unavailable on this platform" };
+            render_placeholder(f, top_chunks[0], message);
+        }
     } else {
-        format!(" Search: '{}' ", app.search_query)
-    };
+        // Context switches are only available from /proc/stat, temperature only with sensors
+        let show_ctxt = cfg!(target_os = "linux");
+        let graph_count = 2 + show_ctxt as u32 + app.temperature.is_some() as u32;
+        let graph_chunks = Layout::default()
+            .direction(along)
+            .constraints(vec![Constraint::Ratio(1, graph_count); graph_count as usize])
+            .split(top_chunks[0]);
 
-    let table = Table::new(rows, [
-        Constraint::Length(6), // PID
-        Constraint::Percentage(40),
-        Constraint::Percentage(25),
-        Constraint::Percentage(25),
-    ])
-    .header(Row::new(vec!["PID", "Name", "CPU", "MEM"]).style(Style::default().fg(theme.border)))
-    .block(Block::default().title(table_title).borders(Borders::ALL).border_style(Style::default().fg(theme.border)))
-    .row_highlight_style(Style::default().bg(theme.highlight_bg).fg(theme.highlight_fg).add_modifier(Modifier::BOLD));
+        if app.cpu_graph == CpuGraph::PerCore {
+            render_stacked_cpu_chart(f, app, &theme, graph_chunks[0]);
+        } else if app.cpu_graph == CpuGraph::Breakdown {
+            render_cpu_breakdown_chart(f, app, &theme, graph_chunks[0]);
+        } else if app.real_time_axis {
+            render_timed_history(f, app, &theme, &app.cpu_history, "CPU", theme.graph_cpu, graph_chunks[0]);
+        } else if app.gridded_graphs {
+            render_gridded_history(f, app, &theme, &app.cpu_history, "CPU", theme.graph_cpu, graph_chunks[0]);
+        } else {
+            render_history_sparkline(f, app, &theme, &app.cpu_history, "CPU", theme.graph_cpu, graph_chunks[0]);
+        }
 
-    f.render_stateful_widget(table, process_chunks[0], &mut app.process_state);
+        if app.real_time_axis {
+            render_timed_history(f, app, &theme, &app.mem_history, "Mem", theme.graph_mem, graph_chunks[1]);
+        } else if app.gridded_graphs {
+            render_gridded_history(f, app, &theme, &app.mem_history, "Mem", theme.graph_mem, graph_chunks[1]);
+        } else {
+            render_history_sparkline(f, app, &theme, &app.mem_history, "Mem", theme.graph_mem, graph_chunks[1]);
+        }
 
-    // Search Input Box
-    let input_style = match app.input_mode {
-        InputMode::Editing => Style::default().fg(theme.highlight_bg),
-        _ => Style::default().fg(Color::DarkGray),
-    };
-    
-    let search_text = if app.input_mode == InputMode::Editing {
-        format!("Search: {}_", app.search_query)
-    } else {
-        format!("Search: {} (Press '/')", app.search_query)
-    };
+        if show_ctxt {
+            render_history_sparkline(f, app, &theme, &app.ctxt_history, "Context Switches / s", theme.graph_cpu, graph_chunks[2]);
+        }
 
-    f.render_widget(Paragraph::new(search_text).style(input_style).block(Block::default().borders(Borders::ALL).title(" Filter ").border_style(Style::default().fg(theme.border))), process_chunks[1]);
+        if let Some(celsius) = app.temperature {
+            let temp_title = format!("Temp {:.0}°C", celsius);
+            render_history_sparkline(f, app, &theme, &app.temp_history, &temp_title, theme.gauge_cpu_high, graph_chunks[graph_chunks.len() - 1]);
+        }
+    }
+
+    // Processes List (Right)
+    render_process_panel(f, app, &theme, top_chunks[1]);
 
     // 3. Gauges
     let gauge_chunks = Layout::default()
@@ -449,84 +4983,216 @@ fn ui(f: &mut ratatui::Frame, app: &mut App) {
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(chunks[2]);
 
-    let cpu_val = *app.cpu_history.back().unwrap_or(&0);
-    f.render_widget(Gauge::default().block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.border))).percent(cpu_val as u16).label(format!("CPU: {}%", cpu_val)).gauge_style(Style::default().fg(if cpu_val > 80 { theme.gauge_cpu_high } else { theme.gauge_cpu_low })), gauge_chunks[0]);
+    // Unacknowledged alerts flash the gauge border in step with the spinner
+    let flash_on = app.spinner_frame.is_multiple_of(2);
+    let alert_border = |alert: &Alert| {
+        if alert.flashing() && flash_on {
+            Style::default().fg(theme.gauge_cpu_high).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Style::default().fg(theme.border)
+        }
+    };
+    let alert_title = |alert: &Alert| if alert.flashing() { " ALERT ([A] to acknowledge) " } else { "" };
+
+    // The average can look calm while one core is pegged by a single-threaded job
+    let busiest_history = max_core_history(&app.core_history);
+    let (cpu_history, cpu_val, cpu_label) = match app.core_history.iter().enumerate().max_by_key(|(_, history)| history.back().copied().unwrap_or(0)) {
+        Some((core, history)) if app.cpu_gauge_max_core => {
+            let cpu_val = history.back().copied().unwrap_or(0);
+            (&busiest_history, cpu_val, format!("CPU busiest core: {}% (CPU{}, avg {}%)", cpu_val, core, history_average(&busiest_history)))
+        }
+        _ => {
+            let cpu_val = *app.cpu_history.back().unwrap_or(&0);
+            (&app.cpu_history, cpu_val, format!("CPU: {}% (avg {}%)", cpu_val, history_average(&app.cpu_history)))
+        }
+    };
+    f.render_widget(Gauge::default().block(Block::default().title(alert_title(&app.cpu_alert)).borders(Borders::ALL).border_style(alert_border(&app.cpu_alert))).percent(cpu_val.min(100) as u16).label(cpu_label).gauge_style(Style::default().fg(if cpu_val > 80 { theme.gauge_cpu_high } else { theme.gauge_cpu_low })), gauge_chunks[0]);
+    let peak_style = Style::default().fg(theme.gauge_cpu_high).add_modifier(Modifier::BOLD);
+    render_peak_marker(f, gauge_chunks[0], history_peak(cpu_history), peak_style);
 
     let mem_val = *app.mem_history.back().unwrap_or(&0);
-    f.render_widget(Gauge::default().block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.border))).percent(mem_val as u16).label(format!("MEM: {}%", mem_val)).gauge_style(Style::default().fg(theme.gauge_mem)), gauge_chunks[1]);
+    let mem_avg = history_average(&app.mem_history);
+    let swap_val = app.swap_percent;
+    let (mem_gauge, mem_label) = if app.invert_gauges {
+        (100 - mem_val.min(100), format!("MEM free: {}% (avg {}%) SWAP free: {}%", 100 - mem_val.min(100), 100 - mem_avg.min(100), 100 - swap_val.min(100)))
+    } else {
+        (mem_val, format!("MEM: {}% (avg {}%) SWAP: {}%", mem_val, mem_avg, swap_val))
+    };
+    let mem_label = if app.mem_stale { format!("{} (stale)", mem_label) } else { mem_label };
+    f.render_widget(Gauge::default().block(Block::default().title(alert_title(&app.mem_alert)).borders(Borders::ALL).border_style(alert_border(&app.mem_alert))).percent(mem_gauge as u16).label(mem_label).gauge_style(Style::default().fg(theme.gauge_mem)), gauge_chunks[1]);
+    // Inverted, the mark holds the lowest free memory instead
+    let mem_peak = history_peak(&app.mem_history).min(100);
+    render_peak_marker(f, gauge_chunks[1], if app.invert_gauges { 100 - mem_peak } else { mem_peak }, peak_style);
 
     // 4. Bottom Section
     let bottom_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .direction(across)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(25), Constraint::Percentage(40)])
         .split(chunks[3]);
 
+    let highlight_style = Style::default().bg(theme.highlight_bg).fg(theme.highlight_fg).add_modifier(Modifier::BOLD);
+
     // Disk
+    let visible_disks: Vec<DiskInfo> = app.visible_disks().into_iter().cloned().collect();
+    // Per-disk I/O comes from /proc/diskstats, so it only has columns where that exists
+    let show_disk_io = !app.disk_io.is_empty();
     let mut disk_rows = Vec::new();
-    for disk in &app.disks {
-        let total = disk.total_space();
-        let available = disk.available_space();
-        let used = total - available;
-        let percent = if total > 0 { (used as f64 / total as f64 * 100.0) as u16 } else { 0 };
-        disk_rows.push(Row::new(vec![
-            format!("{:?}", disk.mount_point()),
-            format!("{:.1} GB", total as f64 / 1_073_741_824.0),
-            format!("{}%", percent),
-        ]).style(Style::default().fg(theme.text)));
-    }
-    f.render_widget(Table::new(disk_rows, [Constraint::Percentage(40), Constraint::Percentage(30), Constraint::Percentage(30)]).block(Block::default().title(" Disks ").borders(Borders::ALL).border_style(Style::default().fg(theme.border))), bottom_chunks[0]);
-
-    // Network Sparklines
+    for disk in &visible_disks {
+        let percent = disk_used_percent(disk) as u16;
+        // Flag inode exhaustion when it's the inodes, not the space, running out
+        let inode_cell = match app.inode_usage.get(&disk.mount) {
+            Some(&inodes) if inodes >= INODE_WARN && percent < INODE_WARN => {
+                Cell::from(format!("{}%", inodes)).style(Style::default().fg(theme.gauge_cpu_high).add_modifier(Modifier::BOLD))
+            }
+            Some(&inodes) => Cell::from(format!("{}%", inodes)),
+            None => Cell::from("-"),
+        };
+        let mut cells = vec![
+            Cell::from(format!("{:?}", disk.mount)),
+            Cell::from(app.disk_units.format_size(disk.total)),
+            Cell::from(format!("{}%", if app.invert_gauges { 100 - percent.min(100) } else { percent })),
+            inode_cell,
+        ];
+        if show_disk_io {
+            match app.disk_io.get(&disk.device) {
+                Some(&(read, written)) => cells.extend([Cell::from(format_rate(read, app.disk_units)), Cell::from(format_rate(written, app.disk_units))]),
+                None => cells.extend([Cell::from("-"), Cell::from("-")]),
+            }
+        }
+        let style = Style::default().fg(theme.text);
+        disk_rows.push(Row::new(cells).style(if disk.stale { style.add_modifier(Modifier::DIM) } else { style }));
+    }
+    let disks_focused = app.focused_panel == FocusedPanel::Disks;
+    // While focused, the selected disk's usage history is drawn under the table
+    let disk_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(if disks_focused { 5 } else { 0 })])
+        .split(bottom_chunks[0]);
+    let mut disks_title = if app.only_full_disks { format!(" Disks ≥{}% used ", app.disk_full_threshold) } else { " Disks ".to_string() };
+    let stale_disks = visible_disks.iter().filter(|disk| disk.stale).count();
+    if stale_disks > 0 {
+        disks_title.push_str(&format!("({} stale) ", stale_disks));
+    }
+    let mut disk_header = vec!["Mount", "Size", if app.invert_gauges { "Free" } else { "Used" }, "Inodes"];
+    let disk_widths = if show_disk_io {
+        disk_header.extend(["Read/s", "Write/s"]);
+        vec![Constraint::Percentage(28), Constraint::Percentage(16), Constraint::Percentage(10), Constraint::Percentage(12), Constraint::Percentage(17), Constraint::Percentage(17)]
+    } else {
+        vec![Constraint::Percentage(40), Constraint::Percentage(24), Constraint::Percentage(18), Constraint::Percentage(18)]
+    };
+    let disk_table = Table::new(disk_rows, disk_widths)
+        .header(Row::new(disk_header).style(Style::default().fg(theme.border)))
+        .block(panel_block(disks_title, &theme, disks_focused))
+        .row_highlight_style(if disks_focused { highlight_style } else { Style::default() });
+    let selected_disk = visible_disks.get(app.disk_state.selected().unwrap_or(0));
+    let no_full_disks = visible_disks.is_empty() && !app.disk_info.is_empty();
+    f.render_stateful_widget(disk_table, disk_chunks[0], &mut app.disk_state);
+    if app.disk_info.is_empty() {
+        render_placeholder(f, disk_chunks[0], "Disk info unavailable on this platform");
+    } else if no_full_disks {
+        render_placeholder(f, disk_chunks[0], &format!("No disks above {}% used", app.disk_full_threshold));
+    }
+
+    if disks_focused {
+        if let Some(history) = selected_disk.and_then(|d| app.disk_history.get(&d.mount)) {
+            // Sparklines draw from the oldest sample, so keep only what fits to show the latest
+            let width = disk_chunks[1].width.saturating_sub(2) as usize;
+            let disk_data: Vec<u64> = history.iter().skip(history.len().saturating_sub(width)).cloned().collect();
+            let title = format!(" {} usage ", selected_disk.map(|d| d.mount.display().to_string()).unwrap_or_default());
+            f.render_widget(Sparkline::default().block(Block::default().title(title).borders(Borders::ALL).border_style(Style::default().fg(theme.border))).data(&disk_data).max(100).style(Style::default().fg(theme.graph_mem)), disk_chunks[1]);
+        }
+    }
+
+    // Per-Core Usage
+    let cores_focused = app.focused_panel == FocusedPanel::Cores;
+    let cpu_count = app.core_history.len();
+    let physical_cores = if app.remote.is_some() { None } else { app.system.physical_core_count() };
+    let cores_title = match physical_cores {
+        Some(physical) => format!(" Cores ({} cores / {} threads) ", physical, cpu_count),
+        None => format!(" Cores ({} threads) ", cpu_count),
+    };
+    // Flag when the temperature column is one shared reading rather than per core
+    let cores_title = if app.show_core_temps && app.core_temps.is_empty() && app.temperature.is_some() {
+        format!("{}· package temp ", cores_title)
+    } else {
+        cores_title
+    };
+    let cores_title = if app.busiest_cores && !app.core_heatmap && cpu_count > BUSIEST_CORES {
+        format!("{}· busiest {} ", cores_title, BUSIEST_CORES)
+    } else {
+        cores_title
+    };
+    let cores_block = panel_block(cores_title, &theme, cores_focused);
+    if app.core_heatmap {
+        render_core_heatmap(f, app, cores_block, bottom_chunks[1]);
+    } else {
+        let row_highlight = if cores_focused { highlight_style } else { Style::default() };
+        render_core_list(f, app, &theme, cores_block, row_highlight, bottom_chunks[1]);
+    }
+    if app.core_history.is_empty() {
+        render_placeholder(f, bottom_chunks[1], "Per-core info unavailable");
+    }
+
+    // Network: Sparklines + Interfaces
+    let network_focused = app.focused_panel == FocusedPanel::Network;
     let net_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(bottom_chunks[1]);
+        .split(bottom_chunks[2]);
 
-    let rx_data: Vec<u64> = app.net_rx_history.iter().cloned().collect();
-    f.render_widget(Sparkline::default().block(Block::default().title(" Network RX ").borders(Borders::ALL).border_style(Style::default().fg(theme.border))).data(&rx_data).style(Style::default().fg(theme.graph_net_rx)), net_chunks[0]);
+    let spark_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(net_chunks[0]);
 
-    let tx_data: Vec<u64> = app.net_tx_history.iter().cloned().collect();
-    f.render_widget(Sparkline::default().block(Block::default().title(" Network TX ").borders(Borders::ALL).border_style(Style::default().fg(theme.border))).data(&tx_data).style(Style::default().fg(theme.graph_net_tx)), net_chunks[1]);
+    let (rx_history, tx_history, rx_title, tx_title) = if app.net_cumulative {
+        (&app.net_rx_total_history, &app.net_tx_total_history, "Network RX total B", "Network TX total B")
+    } else {
+        (&app.net_rx_history, &app.net_tx_history, "Network RX B/s", "Network TX B/s")
+    };
+    render_history_sparkline(f, app, &theme, rx_history, rx_title, theme.graph_net_rx, spark_chunks[0]);
+    render_history_sparkline(f, app, &theme, tx_history, tx_title, theme.graph_net_tx, spark_chunks[1]);
 
-    // 5. Process Details Popup (Modal)
-    if app.input_mode == InputMode::Details {
-        if let Some(pid) = app.selected_pid {
-            if let Some(process) = app.system.process(pid) {
-                let area = centered_rect(60, 50, f.area());
-                f.render_widget(Clear, area); // Clear background
-                
-                let block = Block::default()
-                    .title(" Process Details (Esc to Close) ")
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(theme.border).bg(theme.bg))
-                    .style(Style::default().bg(theme.bg));
-                f.render_widget(block.clone(), area);
+    let interface_rows: Vec<Row> = app.interfaces.iter().map(|iface| {
+        let style = if iface.up {
+            Style::default().fg(theme.text)
+        } else {
+            Style::default().fg(Color::DarkGray).add_modifier(Modifier::DIM)
+        };
+        Row::new(vec![
+            iface.name.clone(),
+            if iface.up { "UP".to_string() } else { "DOWN".to_string() },
+            format!("{:.1} KB/s", iface.rx_rate / 1024.0),
+            format!("{:.1} KB/s", iface.tx_rate / 1024.0),
+            iface.ips.first().cloned().unwrap_or_default(),
+        ])
+        .style(style)
+    }).collect();
 
-                // Use inner area for content to avoid overlap with borders
-                let content_area = block.inner(area);
+    // Full MAC/IP details for the selected interface
+    let interface_details = app.interfaces.get(app.network_state.selected().unwrap_or(0))
+        .map(|iface| {
+            let mac = if iface.mac.is_empty() { "no MAC".to_string() } else { iface.mac.clone() };
+            let ips = if iface.ips.is_empty() { "no address".to_string() } else { iface.ips.join(", ") };
+            format!(" {} | {} ", mac, ips)
+        })
+        .unwrap_or_default();
 
-                let cmd = process.cmd().join(" ");
-                let details_text = vec![
-                    Line::from(vec![Span::styled("PID: ", Style::default().fg(theme.border)), Span::styled(pid.to_string(), Style::default().fg(theme.text))]),
-                    Line::from(vec![Span::styled("Name: ", Style::default().fg(theme.border)), Span::styled(process.name(), Style::default().fg(theme.text))]),
-                    Line::from(vec![Span::styled("Status: ", Style::default().fg(theme.border)), Span::styled(format!("{:?}", process.status()), Style::default().fg(theme.text))]),
-                    Line::from(vec![Span::styled("CPU Usage: ", Style::default().fg(theme.border)), Span::styled(format!("{:.2}%", process.cpu_usage()), Style::default().fg(theme.text))]),
-                    Line::from(vec![Span::styled("Memory: ", Style::default().fg(theme.border)), Span::styled(format!("{:.1} MB", process.memory() as f64 / 1_048_576.0), Style::default().fg(theme.text))]),
-                    Line::from(vec![Span::styled("Virtual Mem: ", Style::default().fg(theme.border)), Span::styled(format!("{:.1} MB", process.virtual_memory() as f64 / 1_048_576.0), Style::default().fg(theme.text))]),
-                    Line::from(vec![Span::styled("Start Time: ", Style::default().fg(theme.border)), Span::styled(format!("{}s ago", System::uptime().saturating_sub(process.start_time())), Style::default().fg(theme.text))]),
-                    Line::from(vec![Span::styled("Disk Read: ", Style::default().fg(theme.border)), Span::styled(format!("{:.1} KB", process.disk_usage().read_bytes as f64 / 1024.0), Style::default().fg(theme.text))]),
-                    Line::from(vec![Span::styled("Disk Write: ", Style::default().fg(theme.border)), Span::styled(format!("{:.1} KB", process.disk_usage().written_bytes as f64 / 1024.0), Style::default().fg(theme.text))]),
-                    Line::from(""),
-                    Line::from(vec![Span::styled("Command: ", Style::default().fg(theme.border))]),
-                    Line::from(Span::styled(cmd, Style::default().fg(theme.text))),
-                ];
-
-                let p = Paragraph::new(details_text)
-                    .wrap(Wrap { trim: true });
-                
-                f.render_widget(p, content_area);
-            }
-        }
+    let interface_table = Table::new(interface_rows, [
+        Constraint::Length(10),
+        Constraint::Length(5),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Min(0),
+    ])
+        .header(Row::new(vec!["Iface", "Link", "RX", "TX", "Address"]).style(Style::default().fg(theme.border)))
+        .block(panel_block(" Interfaces ".to_string(), &theme, network_focused).title_bottom(interface_details))
+        .row_highlight_style(if network_focused { highlight_style } else { Style::default() });
+    f.render_stateful_widget(interface_table, net_chunks[1], &mut app.network_state);
+    if app.interfaces.is_empty() {
+        render_placeholder(f, net_chunks[1], "No network interfaces detected");
     }
+
+    render_popups(f, app, &theme);
 }
 